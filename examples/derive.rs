@@ -8,8 +8,10 @@ use std::io::stdout;
 use std::iter::once;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use supplement::completion::{Ready, Unready};
-use supplement::{Completion, CompletionGroup, Seen, Shell, Supplement, helper::id_no_assoc as id};
+use supplement::completion::{Directive, Ready, Unready};
+use supplement::{
+    Completion, CompletionGroup, Seen, Shell, Supplement, helper::id_no_assoc as id, tokenize,
+};
 
 mod args {
     // Here list some not-so-trivial stuff in the definition, and they're all supported by `supplement`.
@@ -127,8 +129,10 @@ fn main() {
     };
 
     log::info!("Mode #2: completion");
-    let args = args[2..].iter().map(String::from);
-    let (seen, grp) = Git::supplement(args).unwrap();
+    // The words are passed as typed by some shells
+    let mut args = args[2..].to_vec();
+    let quote = tokenize::unquote_args(shell, &mut args);
+    let (seen, grp) = Git::supplement(args.into_iter()).unwrap();
     let ready = match grp {
         CompletionGroup::Ready(r) => {
             // The easy path. No custom logic needed.
@@ -140,12 +144,14 @@ fn main() {
             handle_comp(unready, id, seen, &value, 0)
         }
     };
-    ready.print(shell, &mut stdout()).unwrap();
+    ready.quote(quote).print(shell, &mut stdout()).unwrap();
 }
 
 fn handle_comp(unready: Unready, id: GitID, mut seen: Seen, val: &str, alias_len: usize) -> Ready {
     let comps = match id {
-        id!(GitID.git_dir) | id!(GitID.sub SubID.Log.exclude) => std::process::exit(1), // Exit to use default completion
+        // Let the shell complete the paths
+        id!(GitID.git_dir) => return unready.to_directive(Directive::Dirs),
+        id!(GitID.sub SubID.Log.exclude) => return unready.to_directive(Directive::Files),
         id!(GitID.sub SubID.Checkout.file_or_commit) => {
             // For the first argument, it can either be a git commit or a file
             get_commits(10).chain(get_files()).collect()
@@ -33,30 +33,55 @@ BIN_FILE=$USR_PATH/bin/qit
 echo Installing binary to $BIN_FILE...
 cp $BIN $BIN_FILE
 
-read -p "Please provide shell to install completion file [fish/bash/zsh/all] " SELECT_SHELL
+read -p "Please provide shell to install completion file [fish/bash/zsh/powershell/nushell/elvish/xonsh/all] " SELECT_SHELL
 
 install_comp() {
     SELECT_SHELL="$@"
     case $SELECT_SHELL in
-        fish) COMP_FILE=$(realpath ~/.config/fish/completions/qit.fish)
+        fish) COMP_FILE=~/.config/fish/completions/qit.fish
             ;;
         bash) COMP_FILE=$USR_PATH/share/bash-completion/completions/qit
             ;;
         zsh) COMP_FILE=$USR_PATH/local/share/zsh/site-functions/_qit
             ;;
+        powershell) COMP_FILE=~/.config/powershell/qit.ps1
+            SCRIPT=qit.ps1
+            ;;
+        nushell) COMP_FILE=~/.config/nushell/qit.nu
+            SCRIPT=qit.nu
+            ;;
+        elvish) COMP_FILE=~/.config/elvish/lib/qit.elv
+            SCRIPT=qit.elv
+            ;;
+        xonsh) COMP_FILE=~/.config/xonsh/rc.d/qit.xsh
+            SCRIPT=qit.xsh
+            ;;
         all)
             install_comp fish
             install_comp bash
             install_comp zsh
+            install_comp powershell
+            install_comp nushell
+            install_comp elvish
+            install_comp xonsh
             return
             ;;
         *) echo Unknown shell $SELECT_SHELL; exit 1
     esac
 
     echo Installing completion file to $COMP_FILE...
-    cp shell/qit.$SELECT_SHELL $COMP_FILE
+    mkdir -p $(dirname $COMP_FILE)
+    cp shell/${SCRIPT:-qit.$SELECT_SHELL} $COMP_FILE
     BIN_FILE_ESC=$(echo $BIN_FILE | sed -e "s/\//\\\\\//g")
     sed -i -e "s/PLACEHOLDER_FOR_BIN_PATH/$BIN_FILE_ESC/g" $COMP_FILE
+    if [ "$SELECT_SHELL" = "powershell" ]; then
+        echo "Please add \". $COMP_FILE\" to your \$PROFILE"
+    elif [ "$SELECT_SHELL" = "nushell" ]; then
+        echo "Please add \"source $COMP_FILE\" to your config.nu"
+    elif [ "$SELECT_SHELL" = "elvish" ]; then
+        echo "Please add \"use qit\" to your rc.elv"
+    fi
+    unset SCRIPT
 }
 
 install_comp $SELECT_SHELL
@@ -0,0 +1,46 @@
+Register-ArgumentCompleter -Native -CommandName qit -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $words = @($commandAst.CommandElements |
+        Where-Object { $_.Extent.StartOffset -lt $cursorPosition } |
+        ForEach-Object {
+            if ($_ -is [System.Management.Automation.Language.StringConstantExpressionAst]) {
+                $_.Value
+            } else {
+                $_.Extent.Text
+            }
+        })
+    if ($wordToComplete -eq '') {
+        # preserve the last white space
+        $words += ''
+    }
+
+    $lines = & 'PLACEHOLDER_FOR_BIN_PATH' powershell @words
+    if ($LASTEXITCODE -ne 0) {
+        # return nothing to fall back to default completion
+        return
+    }
+
+    foreach ($line in $lines) {
+        $value, $text, $tooltip = $line -split "`t", 3
+        if ($value -ne '') {
+            [System.Management.Automation.CompletionResult]::new($value, $text, 'ParameterValue', $tooltip)
+            continue
+        }
+
+        # A line without value is a directive: "`t<kind>`t<prefix>`t<arg>`t<glob>"
+        $kind, $prefix, $arg, $glob = ($line -split "`t")[1..4]
+        if ($kind -eq 'nothing') {
+            continue
+        }
+        [System.Management.Automation.CompletionCompleters]::CompleteFilename($arg) |
+            Where-Object {
+                $_.ResultType -eq 'ProviderContainer' -or $kind -eq 'files' -or
+                ($kind -eq 'glob' -and (Split-Path -Leaf $_.ListItemText) -like $glob)
+            } |
+            ForEach-Object {
+                [System.Management.Automation.CompletionResult]::new(
+                    $prefix + $_.CompletionText, $_.ListItemText, $_.ResultType, $_.ToolTip)
+            }
+    }
+}
@@ -6,6 +6,9 @@ pub type ClapCommand<'a> = &'a mut clap::Command<'static>;
 pub type ClapCommand<'a> = &'a mut clap::Command;
 
 pub use clap::ArgAction;
+#[cfg(feature = "clap-4")]
+use clap::error::ErrorKind;
+use std::collections::HashMap;
 
 #[cfg(feature = "clap-3")]
 #[derive(Clone, Copy)]
@@ -31,6 +34,30 @@ pub type Id = str;
 #[cfg(feature = "clap-4")]
 pub type Id = clap::Id;
 
+/// A boolean flag `--x` and its negation `--no-x`.
+#[derive(Clone, Copy)]
+pub(crate) struct Negation<'a> {
+    pub positive: Arg<'a>,
+    pub negative: Arg<'a>,
+    /// Whether the later one overrides the earlier, i.e. they don't conflict with each other.
+    pub overridable: bool,
+}
+
+/// How the args of a command relate to each other, keyed by the arg ID. See [`Command::get_relations`].
+pub(crate) struct Relations<'a> {
+    pub conflicts: HashMap<&'a Id, Vec<Arg<'a>>>,
+    pub requirements: HashMap<&'a Id, Requirement<'a>>,
+    pub negations: HashMap<&'a Id, Negation<'a>>,
+}
+
+/// When an arg is required, see [`Command::get_requirements`].
+pub(crate) enum Requirement<'a> {
+    Always,
+    /// i.e. the other members of a required `ArgGroup`
+    #[cfg_attr(feature = "clap-3", allow(dead_code))]
+    UnlessPresent(Vec<Arg<'a>>),
+}
+
 impl<'a> Command<'a> {
     pub fn get_arguments(&self) -> impl Iterator<Item = Arg<'a>> {
         #[cfg(feature = "clap-3")]
@@ -58,9 +85,15 @@ impl<'a> Command<'a> {
     pub fn get_name(&self) -> &'a str {
         self.0.get_name()
     }
+    pub fn get_all_aliases(&self) -> Vec<&'a str> {
+        self.0.get_all_aliases().collect()
+    }
     pub fn get_about(&self) -> Option<String> {
         self.0.get_about().map(|s| s.to_string())
     }
+    pub fn is_hide_set(&self) -> bool {
+        self.0.is_hide_set()
+    }
     pub fn is_allow_external_subcommands_set(&self) -> bool {
         self.0.is_allow_external_subcommands_set()
     }
@@ -73,6 +106,216 @@ impl<'a> Command<'a> {
     pub fn is_disable_help_subcommand_set(&self) -> bool {
         self.0.is_disable_help_subcommand_set()
     }
+    pub fn is_infer_long_args_set(&self) -> bool {
+        #[cfg(feature = "clap-3")]
+        {
+            #[allow(deprecated)]
+            self.0.is_set(clap::AppSettings::InferLongArgs)
+        }
+        #[cfg(feature = "clap-4")]
+        {
+            // clap 4 doesn't tell us, so try parsing an abbreviated flag.
+            let args: Vec<_> = self.0.get_arguments().collect();
+            let longs = args.iter().flat_map(|a| {
+                let aliases = a.get_all_aliases().unwrap_or_default();
+                a.get_long()
+                    .into_iter()
+                    .chain(aliases)
+                    .map(move |l| (*a, l))
+            });
+            let longs: Vec<_> = longs.collect();
+            let Some((arg, abbr)) = find_abbr(&longs) else {
+                return false;
+            };
+            let res = self
+                .0
+                .clone()
+                .ignore_errors(true)
+                .try_get_matches_from([self.0.get_name().to_owned(), format!("--{abbr}")]);
+            match res {
+                Ok(m) => m.value_source(arg.get_id().as_str()).is_some(),
+                Err(e) => e.kind() != ErrorKind::UnknownArgument,
+            }
+        }
+    }
+    pub fn is_infer_subcommands_set(&self) -> bool {
+        #[cfg(feature = "clap-3")]
+        {
+            #[allow(deprecated)]
+            self.0.is_set(clap::AppSettings::InferSubcommands)
+        }
+        #[cfg(feature = "clap-4")]
+        {
+            // clap 4 doesn't tell us, so try parsing an abbreviated subcommand.
+            let names = self.0.get_subcommands().flat_map(|c| {
+                std::iter::once(c.get_name())
+                    .chain(c.get_all_aliases())
+                    .map(move |n| (c, n))
+            });
+            let names: Vec<_> = names.collect();
+            let Some((sub, abbr)) = find_abbr(&names) else {
+                return false;
+            };
+            let res = self
+                .0
+                .clone()
+                .ignore_errors(true)
+                .try_get_matches_from([self.0.get_name(), abbr]);
+            match res {
+                Ok(m) => m.subcommand_name() == Some(sub.get_name()),
+                // The error must come from the subcommand
+                Err(e) => !matches!(
+                    e.kind(),
+                    ErrorKind::InvalidSubcommand | ErrorKind::UnknownArgument
+                ),
+            }
+        }
+    }
+    /// Everything about how the args relate to each other, which is computed once per command.
+    pub fn get_relations(&self) -> Relations<'a> {
+        Relations {
+            conflicts: self.get_conflicts(),
+            requirements: self.get_requirements(),
+            negations: self.get_negations(),
+        }
+    }
+    /// The negation pairs by the `--no-` prefix convention, keyed by the ID of both flags.
+    pub fn get_negations(&self) -> HashMap<&'a Id, Negation<'a>> {
+        let flags: Vec<_> = self
+            .get_arguments()
+            .filter(|a| !a.is_positional() && !a.takes_values() && !a.is_global_set())
+            .collect();
+        let mut negations = HashMap::new();
+        for negative in flags.iter() {
+            let Some(long) = negative.get_long().and_then(|l| l.strip_prefix("no-")) else {
+                continue;
+            };
+            let Some(positive) = flags.iter().find(|a| a.get_long() == Some(long)) else {
+                continue;
+            };
+            let conflicts_with = |a: &Arg<'a>, b: &Arg<'a>| {
+                let conflicts = self.0.get_arg_conflicts_with(a.0);
+                conflicts.iter().any(|c| c.get_id() == b.get_id())
+            };
+            let overridable =
+                !conflicts_with(positive, negative) && !conflicts_with(negative, positive);
+            let negation = Negation {
+                positive: *positive,
+                negative: *negative,
+                overridable,
+            };
+            negations.insert(positive.get_id(), negation);
+            negations.insert(negative.get_id(), negation);
+        }
+        negations
+    }
+    /// The args in conflict with each arg in either direction, including the other members of its exclusive groups,
+    /// keyed by the arg ID. Global args are ignored.
+    pub fn get_conflicts(&self) -> HashMap<&'a Id, Vec<Arg<'a>>> {
+        let args: Vec<_> = self
+            .get_arguments()
+            .filter(|a| !a.is_global_set())
+            .collect();
+        let mut pairs = vec![];
+        for arg in args.iter() {
+            for other in self.0.get_arg_conflicts_with(arg.0) {
+                pairs.push((arg.get_id(), other.get_id()));
+                pairs.push((other.get_id(), arg.get_id()));
+            }
+        }
+        // clap 3 doesn't tell us about the groups
+        #[cfg(feature = "clap-4")]
+        for group in self.0.get_groups() {
+            if group.clone().is_multiple() {
+                continue;
+            }
+            let members: Vec<_> = group.get_args().collect();
+            for a in members.iter() {
+                pairs.extend(members.iter().map(|b| (*a, *b)));
+            }
+        }
+
+        // Keep the args in the order they're defined
+        let index: HashMap<&'a Id, usize> = (args.iter().enumerate())
+            .map(|(i, a)| (a.get_id(), i))
+            .collect();
+        let mut conflicts: HashMap<&'a Id, Vec<usize>> = HashMap::new();
+        for (a, b) in pairs {
+            let (Some(_), Some(&i)) = (index.get(a), index.get(b)) else {
+                continue;
+            };
+            if a != b {
+                conflicts.entry(a).or_default().push(i);
+            }
+        }
+        conflicts
+            .into_iter()
+            .map(|(id, mut others)| {
+                others.sort_unstable();
+                others.dedup();
+                (id, others.into_iter().map(|i| args[i]).collect())
+            })
+            .collect()
+    }
+    /// The requirement of each required arg, keyed by the arg ID. Global args are ignored.
+    ///
+    /// NOTE: clap has no getters for `requires`, `required_if_eq` or `required_unless_present`,
+    /// so only `required` and required groups are known. Clap 3 doesn't tell us about the groups either.
+    pub fn get_requirements(&self) -> HashMap<&'a Id, Requirement<'a>> {
+        let args: Vec<_> = self
+            .get_arguments()
+            .filter(|a| !a.is_global_set())
+            .collect();
+        let mut requirements = HashMap::new();
+        // Any member of a required group will do
+        #[cfg(feature = "clap-4")]
+        for group in self.0.get_groups().filter(|g| g.is_required_set()) {
+            let members: Vec<_> = group.get_args().collect();
+            for arg in args.iter().filter(|a| members.contains(&a.get_id())) {
+                let others = args
+                    .iter()
+                    .filter(|a| a.get_id() != arg.get_id() && members.contains(&a.get_id()));
+                let others: Vec<_> = others.copied().collect();
+                let requirement = if others.is_empty() {
+                    Requirement::Always
+                } else {
+                    Requirement::UnlessPresent(others)
+                };
+                requirements.insert(arg.get_id(), requirement);
+            }
+        }
+        for arg in args.iter().filter(|a| a.is_required_set()) {
+            requirements.insert(arg.get_id(), Requirement::Always);
+        }
+        requirements
+    }
+    /// Whether the flags can be repeated, with the later one overriding the earlier.
+    /// NOTE: clap doesn't tell us about a single arg overriding itself, i.e. `overrides_with` itself.
+    pub fn is_args_override_self(&self) -> bool {
+        #[cfg(feature = "clap-3")]
+        {
+            #[allow(deprecated)]
+            self.0.is_set(clap::AppSettings::AllArgsOverrideSelf)
+        }
+        #[cfg(feature = "clap-4")]
+        {
+            self.0.is_args_override_self()
+        }
+    }
+}
+
+/// Find a name whose abbreviation (without the last character) isn't a prefix of any other name.
+#[cfg(feature = "clap-4")]
+fn find_abbr<'a, T: Copy>(names: &[(T, &'a str)]) -> Option<(T, &'a str)> {
+    names.iter().find_map(|(t, name)| {
+        let (last, _) = name.char_indices().last()?;
+        let abbr = &name[..last];
+        if abbr.is_empty() {
+            return None;
+        }
+        let count = names.iter().filter(|(_, n)| n.starts_with(abbr)).count();
+        (count == 1).then_some((*t, abbr))
+    })
 }
 
 impl<'a> Arg<'a> {
@@ -109,6 +352,18 @@ impl<'a> Arg<'a> {
             self.0.get_num_args().expect("built").min_values()
         }
     }
+    pub fn get_value_delimiter(&self) -> Option<char> {
+        self.0.get_value_delimiter()
+    }
+    pub fn is_last_set(&self) -> bool {
+        self.0.is_last_set()
+    }
+    pub fn is_hide_set(&self) -> bool {
+        self.0.is_hide_set()
+    }
+    pub fn is_required_set(&self) -> bool {
+        self.0.is_required_set()
+    }
     pub fn is_require_equals_set(&self) -> bool {
         self.0.is_require_equals_set()
     }
@@ -141,9 +396,22 @@ impl<'a> Arg<'a> {
     pub fn get_short_and_visible_aliases(&self) -> Option<Vec<char>> {
         self.0.get_short_and_visible_aliases()
     }
+    pub fn get_long(&self) -> Option<&'a str> {
+        self.0.get_long()
+    }
     pub fn get_long_and_visible_aliases(&self) -> Option<Vec<&'a str>> {
         self.0.get_long_and_visible_aliases()
     }
+    pub fn get_hidden_short_aliases(&self) -> Vec<char> {
+        let visible = self.0.get_visible_short_aliases().unwrap_or_default();
+        let all = self.0.get_all_short_aliases().unwrap_or_default();
+        all.into_iter().filter(|a| !visible.contains(a)).collect()
+    }
+    pub fn get_hidden_aliases(&self) -> Vec<&'a str> {
+        let visible = self.0.get_visible_aliases().unwrap_or_default();
+        let all = self.0.get_all_aliases().unwrap_or_default();
+        all.into_iter().filter(|a| !visible.contains(a)).collect()
+    }
     pub fn get_action(&self) -> &ArgAction {
         self.0.get_action()
     }
@@ -4,6 +4,7 @@ pub(crate) struct ArgsContext<'a, ID> {
     args: &'a [Arg<ID>],
     cur_arg_values_count: usize,
     start_idx: usize,
+    options_ended: bool,
 }
 impl<'a, ID> ArgsContext<'a, ID> {
     pub fn new(args: &'a [Arg<ID>]) -> Self {
@@ -11,15 +12,36 @@ impl<'a, ID> ArgsContext<'a, ID> {
             args,
             start_idx: 0,
             cur_arg_values_count: 0,
+            options_ended: false,
         }
     }
+    /// Called when `--` is seen. After that, everything is an arg.
+    pub fn end_options(&mut self) {
+        self.options_ended = true;
+    }
+    pub fn is_options_ended(&self) -> bool {
+        self.options_ended
+    }
     pub fn has_seen_arg(&self) -> bool {
         self.start_idx != 0 || self.cur_arg_values_count != 0
     }
     pub fn next_arg(&mut self) -> Option<&Arg<ID>> {
         log::debug!("next arg called");
+        if self.options_ended {
+            // Everything after `--` goes to the `last` arg, skipping the unfilled ones before it
+            let args = &self.args[self.start_idx..];
+            if let Some(idx) = args.iter().position(|a| a.last).filter(|idx| *idx > 0) {
+                log::info!("skip {idx} args to the last one");
+                self.start_idx += idx;
+                self.cur_arg_values_count = 0;
+            }
+        }
         let args = &self.args[self.start_idx..];
         let next = args.iter().next()?;
+        if next.last && !self.options_ended {
+            log::info!("the next arg only takes values after `--`");
+            return None;
+        }
         if next.max_values == self.cur_arg_values_count + 1 {
             self.start_idx += 1;
             self.cur_arg_values_count = 0;
@@ -34,20 +56,28 @@ impl<'a, ID> ArgsContext<'a, ID> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::core::CowOwned;
+    use crate::core::{CowOwned, CowSlice};
     use crate::id;
 
     const ARG1: Arg<u32> = Arg {
         id: Some(line!()),
         seen_id: id::SingleVal::new(line!()).into(),
         max_values: 1,
+        last: false,
+        value_delimiter: None,
         possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     const ARG2: Arg<u32> = Arg {
         id: Some(line!()),
         seen_id: id::SingleVal::new(line!()).into(),
         max_values: 1,
+        last: false,
+        value_delimiter: None,
         possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     #[test]
     fn test_empty_arg_ctx() {
@@ -70,13 +100,21 @@ mod test {
         id: Some(line!()),
         seen_id: id::MultiVal::new(line!()).into(),
         max_values: 2,
+        last: false,
+        value_delimiter: None,
         possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     const ARG4: Arg<u32> = Arg {
         id: Some(line!()),
         seen_id: id::MultiVal::new(line!()).into(),
         max_values: 3,
+        last: false,
+        value_delimiter: None,
         possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     #[test]
     fn test_var_arg_ctx() {
@@ -94,4 +132,40 @@ mod test {
 
         assert!(ctx.next_arg().is_none());
     }
+
+    const LAST: Arg<u32> = Arg {
+        id: Some(line!()),
+        seen_id: id::MultiVal::new(line!()).into(),
+        max_values: usize::MAX,
+        last: true,
+        value_delimiter: None,
+        possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+    #[test]
+    fn test_last_arg_ctx() {
+        let mut ctx = ArgsContext::new(const { &[ARG1, LAST] });
+        assert_eq!(ctx.next_arg().unwrap().id, ARG1.id);
+        assert!(ctx.next_arg().is_none());
+
+        ctx.end_options();
+        assert_eq!(ctx.next_arg().unwrap().id, LAST.id);
+        assert_eq!(ctx.next_arg().unwrap().id, LAST.id);
+    }
+    #[test]
+    fn test_skip_to_last_arg_ctx() {
+        // e.g. `cmd [opt] -- [last]...`, where `opt` is never given
+        let mut ctx = ArgsContext::new(const { &[ARG1, ARG3, LAST] });
+        assert_eq!(ctx.next_arg().unwrap().id, ARG1.id);
+        assert_eq!(ctx.next_arg().unwrap().id, ARG3.id);
+
+        ctx.end_options();
+        assert_eq!(ctx.next_arg().unwrap().id, LAST.id);
+        assert_eq!(ctx.next_arg().unwrap().id, LAST.id);
+
+        let mut ctx = ArgsContext::new(const { &[ARG1, LAST] });
+        ctx.end_options();
+        assert_eq!(ctx.next_arg().unwrap().id, LAST.id);
+    }
 }
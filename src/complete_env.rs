@@ -0,0 +1,120 @@
+use crate::completion::{Ready, Unready};
+use crate::script::{self, Invoke};
+use crate::tokenize;
+use crate::{CompletionGroup, Seen, Shell, Supplement};
+use std::io::Write;
+
+/// Environment-variable driven completion, so that your binary doesn't have to multiplex
+/// *"normal run"* and *"completion mode"* by hand.
+///
+/// Call [`CompleteEnv::complete`] at the very beginning of `main`.
+/// If the environment variable (say, `COMPLETE`) is not set, it returns and your app runs as usual.
+/// Otherwise, the value of it is the shell, and the binary is in completion mode:
+/// - `COMPLETE=fish my-app` prints the registration script for fish, and exits.
+///   So users just need to put `COMPLETE=fish my-app | source` in their `config.fish`.
+/// - `COMPLETE=fish my-app -- my-app log ''` completes `my-app log <TAB>` for fish, and exits.
+///   This is what the registration script calls.
+///
+/// If the completion fails (e.g. unknown flag, or the shell is not supported), it exits with `1`,
+/// so that the registration script falls back to the shell's default completion.
+///
+/// ```no_run
+/// mod def {
+///     # #[cfg(feature = "clap-3")]
+///     # use clap3 as clap;
+///     # #[cfg(feature = "clap-4")]
+///     # use clap4 as clap;
+///     pub use clap::Parser;
+///     pub use supplement::{CompleteEnv, Completion, Supplement};
+///
+///     #[derive(Parser, Supplement)]
+///     pub enum Git {
+///         Checkout { file_or_commit: String },
+///     }
+/// }
+/// use def::*;
+///
+/// CompleteEnv::new("COMPLETE").complete::<Git>(|_seen, _id, _value, unready| {
+///     // Custom completion logic
+///     unready.to_ready(vec![Completion::new("main", "the main branch")])
+/// });
+///
+/// // Not in completion mode, the app runs as usual.
+/// let git = Git::parse();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CompleteEnv<'a> {
+    var: &'a str,
+}
+
+impl<'a> CompleteEnv<'a> {
+    pub fn new(var: &'a str) -> Self {
+        CompleteEnv { var }
+    }
+
+    /// Do the completion and exit if the environment variable is set. Otherwise, do nothing.
+    ///
+    /// `handle` is called with `(seen, id, value, unready)` when [`CompletionGroup::Unready`] is met.
+    pub fn complete<S: Supplement>(
+        self,
+        handle: impl FnOnce(Seen, S::ID, String, Unready) -> Ready,
+    ) {
+        let shell = match std::env::var(self.var) {
+            Ok(shell) if !shell.is_empty() => shell,
+            _ => return,
+        };
+        let code = match self.run::<S>(&shell, std::env::args(), handle, &mut std::io::stdout()) {
+            Ok(()) => 0,
+            Err(err) => {
+                log::warn!("completion failed: {err}");
+                1
+            }
+        };
+        std::process::exit(code)
+    }
+
+    #[doc(hidden)]
+    pub fn run<S: Supplement>(
+        self,
+        shell: &str,
+        mut args: impl Iterator<Item = String>,
+        handle: impl FnOnce(Seen, S::ID, String, Unready) -> Ready,
+        w: &mut impl Write,
+    ) -> Result<(), String> {
+        let shell: Shell = shell.parse()?;
+        let bin = args.next().unwrap_or_default();
+
+        match args.next().as_deref() {
+            None => {
+                let bin_name = std::path::Path::new(&bin)
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(&bin);
+                let completer_path = std::env::current_exe().map_err(|e| e.to_string())?;
+                let completer_path = completer_path.to_str().ok_or("non UTF-8 binary path")?;
+                let invoke = Invoke::Env {
+                    var: self.var,
+                    completer_path,
+                };
+                script::render_with(shell, bin_name, invoke, w).map_err(|e| e.to_string())
+            }
+            Some("--") => {
+                let mut args: Vec<_> = args.collect();
+                let quote = tokenize::unquote_args(shell, &mut args);
+
+                let (seen, grp) = S::supplement(args.into_iter()).map_err(|e| e.to_string())?;
+                let ready = match grp {
+                    CompletionGroup::Ready(ready) => ready,
+                    CompletionGroup::Unready { unready, id, value } => {
+                        handle(seen, id, value, unready)
+                    }
+                };
+                ready
+                    .quote(quote)
+                    .print(shell, w)
+                    .map_err(|e| e.to_string())
+            }
+            Some(arg) => Err(format!("expect `--` for completion, got `{arg}`")),
+        }
+    }
+}
@@ -7,6 +7,9 @@
 //! An example can be found in `supplement-example`.
 
 use crate::Shell;
+use crate::matcher::{Fuzzy, Match, Matcher};
+use crate::tokenize::Quote;
+use std::borrow::Cow;
 use std::io::Result as IoResult;
 use std::io::Write;
 
@@ -27,17 +30,26 @@ use crate::core::Command; // for documnent
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Completion {
     pub value: String,
+    /// What's shown in the menu. `None` means the same as [`Completion::value`].
+    pub display: Option<String>,
     pub description: String,
-    pub group: Option<&'static str>,
+    pub group: Option<Group>,
     pub always_match: bool,
+    /// Completions with higher priority show first, if the order is [`Order::Priority`].
+    pub priority: i32,
+    /// What to append after the value is inserted. `None` means the shell's default, which is usually a space.
+    pub suffix: Option<String>,
 }
 impl Completion {
     pub fn new(value: impl ToString, description: impl ToString) -> Self {
         Completion {
             value: value.to_string(),
+            display: None,
             description: description.to_string(),
             group: None,
             always_match: false,
+            priority: 0,
+            suffix: None,
         }
     }
     pub fn set_value<F: FnOnce(&str) -> String>(&mut self, val: F) {
@@ -47,20 +59,161 @@ impl Completion {
         self.set_value(val);
         self
     }
+    /// Show `display` in the menu instead of the inserted [`Completion::value`].
+    ///
+    /// NOTE that bash and fish have no such distinction, so they always show the value.
+    pub fn display(mut self, display: impl ToString) -> Self {
+        self.display = Some(display.to_string());
+        self
+    }
+    /// What's shown in the menu.
+    pub fn display_text(&self) -> &str {
+        self.display.as_deref().unwrap_or(&self.value)
+    }
     /// If this is set, this [`Completion`] will always be returned.
     /// Otherwise it will be returned only if [`Completion::value`] matches the value on CLI.
     pub fn always_match(mut self) -> Self {
         self.always_match = true;
         self
     }
-    pub fn group(mut self, group: &'static str) -> Self {
-        self.group = Some(group);
+    /// Put the completion into a group, which can be a plain name, or a [`Group`] with more details.
+    /// ```no_run
+    /// # use supplement::completion::{Completion, Group};
+    /// # let remote = String::from("origin");
+    /// Completion::new("checkout", "").group("command");
+    /// Completion::new("main", "").group(Group::new(remote).description("remote branches").priority(1));
+    /// ```
+    pub fn group(mut self, group: impl Into<Group>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+    /// See [`Completion::priority`] and [`Ready::order`].
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+    /// Append `suffix` instead of a space after the value is inserted.
+    ///
+    /// NOTE that fish has no control over the trailing space. It only omits the space if
+    /// the value ends with characters like `/` or `=`.
+    pub fn suffix(mut self, suffix: impl ToString) -> Self {
+        self.suffix = Some(suffix.to_string());
+        self
+    }
+    /// Don't append a space after the value is inserted, e.g. for `--color=` or `src/`.
+    pub fn no_space(self) -> Self {
+        self.suffix("")
+    }
+
+    /// The value to insert, suffix included.
+    fn value_with_suffix(&self) -> Cow<'_, str> {
+        match self.suffix.as_deref() {
+            None | Some("") => Cow::Borrowed(&self.value),
+            Some(suffix) => Cow::Owned(format!("{}{suffix}", self.value)),
+        }
+    }
+}
+
+/// The group of a [`Completion`], see [`Completion::group`].
+///
+/// Only zsh shows the groups as separate sections. Other shells show the group name in place of an empty description.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Group {
+    pub name: Cow<'static, str>,
+    /// Shown as the heading of the section. The name is used if it's empty.
+    pub description: String,
+    /// Groups with higher priority show first.
+    /// If the same, the group with fewer candidates shows first, and then sort by group name.
+    pub priority: i32,
+}
+impl Group {
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Group {
+            name: name.into(),
+            description: String::new(),
+            priority: 0,
+        }
+    }
+    pub fn description(mut self, description: impl ToString) -> Self {
+        self.description = description.to_string();
+        self
+    }
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
         self
     }
 }
+impl From<&'static str> for Group {
+    fn from(name: &'static str) -> Self {
+        Group::new(name)
+    }
+}
+impl From<String> for Group {
+    fn from(name: String) -> Self {
+        Group::new(name)
+    }
+}
+
+/// Something for the shell to complete with its native mechanism, see [`Ready::directive`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Directive {
+    /// File paths.
+    Files,
+    /// Directories.
+    Dirs,
+    /// Files matching the glob pattern (e.g. `*.rs`). Directories are also completed, so that users can navigate.
+    Glob(String),
+    /// Nothing at all, not even the shell's default completion.
+    Nothing,
+}
+impl Directive {
+    /// The name in line protocols, followed by the glob pattern if any.
+    fn kind(&self) -> &'static str {
+        match self {
+            Directive::Files => "files",
+            Directive::Dirs => "dirs",
+            Directive::Glob(_) => "glob",
+            Directive::Nothing => "nothing",
+        }
+    }
+    fn glob(&self) -> &str {
+        match self {
+            Directive::Glob(glob) => glob,
+            _ => "",
+        }
+    }
+    fn is_path(&self) -> bool {
+        !matches!(self, Directive::Nothing)
+    }
+}
+
+/// How the candidates are ordered in [`Ready::print`], see [`Ready::order`].
+///
+/// NOTE that some shells (Nushell, Elvish and Xonsh) may sort the candidates by themselves anyway.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Order {
+    /// The better the candidate matches (see [`Ready::ranked`]), the earlier it shows.
+    /// The shell may still sort them for displaying.
+    #[default]
+    Score,
+    /// Keep the order as the candidates are given.
+    Insertion,
+    /// Sort by value.
+    Alphabetical,
+    /// Sort by [`Completion::priority`], higher first. If the same, sort by value.
+    Priority,
+}
+impl Order {
+    /// Whether the shell should keep the order instead of sorting again.
+    fn keep(self) -> bool {
+        matches!(self, Order::Insertion | Order::Priority)
+    }
+}
 
 /// The object to represent multiple completion results.
-/// It's solely used to print out those completion results with [`Ready::print`].
+/// It's solely used to print out those completion results with [`Ready::print`] or [`Ready::print_json`].
 ///
 /// The struct should be created directly by [`Command::supplement`],
 /// or by consuming an [`Unready`] with [`Unready::to_ready`].
@@ -68,8 +221,63 @@ impl Completion {
 pub struct Ready {
     arg: String,
     comps: Vec<Completion>,
+    quote: Quote,
+    matcher: Box<dyn Matcher>,
+    /// Prepended to what the directive completes, see [`Unready::to_ready`]
+    prefix: String,
+    directive: Option<Directive>,
+    order: Order,
 }
 impl Ready {
+    pub(crate) fn new(comps: Vec<Completion>, arg: String) -> Self {
+        Ready {
+            arg,
+            comps,
+            quote: Quote::None,
+            matcher: Box::new(Fuzzy),
+            prefix: String::new(),
+            directive: None,
+            order: Order::default(),
+        }
+    }
+
+    /// Let the shell complete something with its native mechanism, such as file paths,
+    /// in addition to the candidates. See also [`Unready::to_directive`].
+    ///
+    /// Some shells (Nushell and Xonsh) can't mix candidates and native completion,
+    /// in which case the native one is only used when no candidate matches,
+    /// and directories or glob patterns fall back to file paths.
+    pub fn directive(mut self, directive: Directive) -> Self {
+        self.directive = Some(directive);
+        self
+    }
+
+    /// Set the strategy to filter and rank the candidates in [`Ready::print`].
+    /// The default is [`Fuzzy`]. See [`crate::matcher`] for the shipped strategies.
+    ///
+    /// NOTE that bash only takes candidates starting with the value on command-line,
+    /// so for bash the matcher can only rule out more candidates.
+    pub fn matcher(mut self, matcher: impl Matcher + 'static) -> Self {
+        self.matcher = Box::new(matcher);
+        self
+    }
+    /// Set the order of candidates in [`Ready::print`]. The default is [`Order::Score`].
+    ///
+    /// For orders other than [`Order::Score`] and [`Order::Alphabetical`], bash and zsh are told not to sort the candidates.
+    /// Fish always keeps the order.
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+    /// Set the quoting state of the word being completed, so that [`Ready::print`] quotes the values accordingly.
+    /// Usually it comes from [`crate::tokenize`]. The default is [`Quote::None`].
+    ///
+    /// Some shells (zsh, fish and Elvish) always quote the values by themselves, where this makes no difference.
+    pub fn quote(mut self, quote: Quote) -> Self {
+        self.quote = quote;
+        self
+    }
+
     #[doc(hidden)]
     pub fn inner(&self) -> (&[Completion], &str) {
         (&self.comps, &self.arg)
@@ -79,71 +287,348 @@ impl Ready {
         (self.comps, self.arg)
     }
 
+    /// Print the completion as a single JSON object, for consumers other than shells
+    /// (editor plugins, fuzzy pickers, test tools...).
+    ///
+    /// Unlike [`Ready::print`], nothing is filtered out. Every candidate is listed in order,
+    /// and the consumer can decide how to use `arg`. For convenience, the result of the matcher
+    /// (see [`Ready::matcher`]) is included as `score` and `positions` (the matched character indices of `value`),
+    /// both of which are `null` if the candidate doesn't match.
+    ///
+    /// If there is a [`Directive`], it's written as `{"kind": "files" | "dirs" | "glob" | "nothing", "prefix": ..., "glob": ...}`,
+    /// where `prefix` should be prepended to the completed paths, and `glob` is an empty string unless the kind is `glob`.
+    ///
+    /// For `git ma<TAB>`, the output looks like (formatted for readability):
+    /// ```json
+    /// {
+    ///     "arg": "ma",
+    ///     "directive": null,
+    ///     "completions": [
+    ///         {"value": "master", "display": "master", "description": "", "group": "branch", "always_match": false, "priority": 0, "suffix": null, "score": 56, "positions": [0, 1]},
+    ///         {"value": "HEAD", "display": "HEAD", "description": "", "group": null, "always_match": true, "priority": 0, "suffix": null, "score": null, "positions": null}
+    ///     ]
+    /// }
+    /// ```
+    pub fn print_json(&self, w: &mut impl Write) -> IoResult<()> {
+        write!(w, "{{\"arg\":")?;
+        write_json_str(w, &self.arg)?;
+        write!(w, ",\"directive\":")?;
+        match &self.directive {
+            Some(d) => {
+                let record = [
+                    ("kind", Json::Str(d.kind())),
+                    ("prefix", Json::Str(&self.prefix)),
+                    ("glob", Json::Str(d.glob())),
+                ];
+                write_json_record(w, &record)?
+            }
+            None => write!(w, "null")?,
+        }
+        write!(w, ",\"completions\":[")?;
+        for (i, comp) in self.comps.iter().enumerate() {
+            if i != 0 {
+                write!(w, ",")?;
+            }
+            write!(w, "{{\"value\":")?;
+            write_json_str(w, &comp.value)?;
+            write!(w, ",\"display\":")?;
+            write_json_str(w, comp.display_text())?;
+            write!(w, ",\"description\":")?;
+            write_json_str(w, &comp.description)?;
+            write!(w, ",\"group\":")?;
+            match &comp.group {
+                Some(group) => write_json_str(w, &group.name)?,
+                None => write!(w, "null")?,
+            }
+            write!(w, ",\"always_match\":{}", comp.always_match)?;
+            write!(w, ",\"priority\":{}", comp.priority)?;
+            write!(w, ",\"suffix\":")?;
+            match &comp.suffix {
+                Some(suffix) => write_json_str(w, suffix)?,
+                None => write!(w, "null")?,
+            }
+            match self.match_comp(comp) {
+                Some(m) => {
+                    let positions: Vec<_> = m.positions.iter().map(|p| p.to_string()).collect();
+                    let positions = positions.join(",");
+                    write!(w, ",\"score\":{},\"positions\":[{positions}]}}", m.score)?
+                }
+                None => write!(w, ",\"score\":null,\"positions\":null}}")?,
+            }
+        }
+        writeln!(w, "]}}")
+    }
+
     /// Print the completion.
     /// Normally this is used to print the completion to stdout in a shell completion script.
+    ///
+    /// Values are escaped or quoted as the shell requires, and tabs or newlines in descriptions are
+    /// replaced by white spaces, so it's safe to have arbitrary file names or commit messages in [`Completion`].
+    ///
+    /// In line-based protocols (bash, zsh, fish and PowerShell), a [`Directive`] is written as a line
+    /// whose value is empty, followed by `<kind>\t<prefix>\t<arg>\t<glob>`.
+    /// For bash, `nospace` is also a kind, meaning some candidates have a [`Completion::suffix`],
+    /// and so is `nosort`, meaning the candidates should be shown in the given order (see [`Ready::order`]).
     pub fn print(&self, shell: Shell, w: &mut impl Write) -> IoResult<()> {
-        let comps = self.comps.iter().filter(|comp| {
-            if shell == Shell::Bash {
-                return comp.value.starts_with(&self.arg); // If there are multiple candates, bash will not complete :(
-            }
-
-            if comp.always_match {
-                return true;
-            }
-            naive_fuzzy(&comp.value, &self.arg)
+        let comps = self.matched(shell);
+        let directive = self.directive.as_ref();
+        let directive_line = directive.map(|d| {
+            let fields = [d.kind(), &self.prefix, &self.arg, d.glob()];
+            let fields: Vec<_> = fields.iter().map(|f| sanitize(f)).collect();
+            fields.join("\t")
         });
+        // If the shell can't mix candidates and native completion, give the native one a chance
+        let fallback = directive.is_some_and(Directive::is_path) && comps.is_empty();
+        let comps = comps.into_iter();
         match shell {
             Shell::Bash => {
+                // With a path directive, the script sets `-o filenames` and bash quotes everything itself
+                let quote = !directive.is_some_and(Directive::is_path);
+                // The trailing space can only be turned off for all candidates
+                let mut no_space = false;
                 for comp in comps {
-                    writeln!(w, "{}", comp.value)?; // Bash doesn't allow description
+                    no_space |= comp.suffix.is_some();
+                    let value = comp.value_with_suffix();
+                    let value = match quote {
+                        true => bash_quote(&value, self.quote).into_owned(),
+                        false => sanitize(&value).into_owned(),
+                    };
+                    writeln!(w, "{value}")?; // Bash doesn't allow description
+                }
+                if no_space {
+                    writeln!(w, "\tnospace\t\t\t")?;
+                }
+                if self.order.keep() {
+                    writeln!(w, "\tnosort\t\t\t")?;
+                }
+                if let Some(line) = directive_line {
+                    writeln!(w, "\t{line}")?;
                 }
             }
             Shell::Fish => {
+                // Fish quotes the values itself
+                for comp in comps {
+                    let value = comp.value_with_suffix();
+                    let value = sanitize(&value);
+                    writeln!(w, "{value}\t{}", sanitize(desc_or_group(comp)))?
+                }
+                if let Some(line) = directive_line {
+                    writeln!(w, "\t{line}")?;
+                }
+            }
+            Shell::PowerShell => {
+                // One `CompletionResult` per line: completion text, list item text and tooltip.
+                // NOTE: PowerShell refuses an empty tooltip, so fall back to the list item text.
+                // PowerShell never appends a space, so the suffix is simply part of the completion text.
                 for comp in comps {
-                    let desc = match (comp.description.as_str(), comp.group) {
-                        ("", None) => "",
-                        ("", Some(g)) => g,
-                        (desc, _) => desc,
+                    let tooltip = match desc_or_group(comp) {
+                        "" => comp.display_text(),
+                        desc => desc,
                     };
-                    writeln!(w, "{}\t{}", comp.value, desc)?
+                    let value =
+                        powershell_quote(&comp.value_with_suffix(), self.quote).into_owned();
+                    let text = sanitize(comp.display_text());
+                    writeln!(w, "{value}\t{text}\t{}", sanitize(tooltip))?
                 }
+                if let Some(line) = directive_line {
+                    writeln!(w, "\t{line}")?;
+                }
+            }
+            Shell::Nushell => {
+                // A list of `{value, description}` records, which is what the external completer expects.
+                // Nushell has no notion of group, so prefix the description with it.
+                // `null` makes nushell fall back to file completion.
+                if fallback {
+                    return writeln!(w, "null");
+                }
+                write!(w, "[")?;
+                for (i, comp) in comps.enumerate() {
+                    if i != 0 {
+                        write!(w, ",")?;
+                    }
+                    let desc = match (comp.description.as_str(), &comp.group) {
+                        (desc, None) => desc.to_owned(),
+                        ("", Some(g)) => g.name.to_string(),
+                        (desc, Some(g)) => format!("{}: {desc}", g.name),
+                    };
+                    let value = nushell_quote(&comp.value_with_suffix(), self.quote).into_owned();
+                    let mut record = vec![
+                        ("value", Json::Str(&value)),
+                        ("description", Json::Str(&desc)),
+                    ];
+                    if let Some(display) = &comp.display {
+                        record.push(("display_override", Json::Str(display)));
+                    }
+                    if comp.suffix.is_some() {
+                        record.push(("append_whitespace", Json::Bool(false)));
+                    }
+                    write_json_record(w, &record)?;
+                }
+                writeln!(w, "]")?;
+            }
+            Shell::Elvish => {
+                // One JSON record per line, to be consumed by `from-json`.
+                // Elvish quotes the candidates itself, so the value is written as is.
+                for comp in comps {
+                    let record = [
+                        ("value", Json::Str(&comp.value)),
+                        ("display", Json::Str(comp.display_text())),
+                        ("description", Json::Str(desc_or_group(comp))),
+                        ("suffix", Json::Str(comp.suffix.as_deref().unwrap_or(" "))),
+                    ];
+                    write_json_record(w, &record)?;
+                    writeln!(w)?;
+                }
+                if let Some(d) = directive {
+                    let regex = glob_to_regex(d.glob());
+                    let record = [
+                        ("directive", Json::Str(d.kind())),
+                        ("prefix", Json::Str(&self.prefix)),
+                        ("arg", Json::Str(&self.arg)),
+                        ("regex", Json::Str(&regex)),
+                    ];
+                    write_json_record(w, &record)?;
+                    writeln!(w)?;
+                }
+            }
+            Shell::Xonsh => {
+                // A list of JSON records, to be turned into `RichCompletion`s by the completer function.
+                // Xonsh inserts the value verbatim, so it has to be quoted here.
+                // `null` makes the completer return nothing, and xonsh falls back to file completion.
+                if fallback {
+                    return writeln!(w, "null");
+                }
+                write!(w, "[")?;
+                for (i, comp) in comps.enumerate() {
+                    if i != 0 {
+                        write!(w, ",")?;
+                    }
+                    let value = xonsh_quote(&comp.value_with_suffix(), self.quote).into_owned();
+                    let record = [
+                        ("value", Json::Str(&value)),
+                        ("display", Json::Str(comp.display_text())),
+                        ("description", Json::Str(desc_or_group(comp))),
+                        ("append_space", Json::Bool(comp.suffix.is_none())),
+                    ];
+                    write_json_record(w, &record)?;
+                }
+                writeln!(w, "]")?;
             }
             Shell::Zsh => {
-                let mut groups: Vec<(&str, Vec<&Completion>)> = vec![];
+                let default_group = Group::new("option");
+                let mut groups: Vec<(&Group, Vec<&Completion>)> = vec![];
                 for comp in comps {
-                    let group = comp.group.unwrap_or("option");
-                    let (_, group_vec) =
-                        if let Some(group_vec) = groups.iter_mut().find(|(s, _)| *s == group) {
-                            group_vec
-                        } else {
-                            groups.push((group, vec![]));
-                            groups.last_mut().unwrap()
-                        };
+                    let group = comp.group.as_ref().unwrap_or(&default_group);
+                    let (_, group_vec) = if let Some(group_vec) =
+                        groups.iter_mut().find(|(g, _)| g.name == group.name)
+                    {
+                        group_vec
+                    } else {
+                        groups.push((group, vec![]));
+                        groups.last_mut().unwrap()
+                    };
                     group_vec.push(comp);
                 }
 
-                // Group with fewer count shows first. If the same, sort by group name
-                groups.sort_by_key(|(k, v)| (v.len(), *k));
+                // Group with higher priority shows first, then the one with fewer count.
+                // If still the same, sort by group name.
+                // For insertion order, the groups keep the order they first appear.
+                if self.order != Order::Insertion {
+                    groups.sort_by(|(g1, v1), (g2, v2)| {
+                        (g2.priority, v1.len(), &g1.name).cmp(&(g1.priority, v2.len(), &g2.name))
+                    });
+                }
 
+                // The group line is `<name>`, `<name>\t<description>`,
+                // or `<name>\t<description>\tunsorted` if zsh should keep the order.
                 for (group, comps) in groups.into_iter() {
-                    writeln!(w, "{}", group)?;
+                    let name = sanitize(&group.name);
+                    let desc = sanitize(&group.description);
+                    match (self.order.keep(), desc.as_ref()) {
+                        (true, desc) => writeln!(w, "{name}\t{desc}\tunsorted")?,
+                        (false, "") => writeln!(w, "{name}")?,
+                        (false, desc) => writeln!(w, "{name}\t{desc}")?,
+                    }
                     for comp in comps.into_iter() {
-                        if comp.description.is_empty() {
-                            writeln!(w, "\t{}\t{}", comp.value, comp.value)?
-                        } else {
-                            writeln!(
-                                w,
-                                "\t{}\t{} -- {}",
-                                comp.value, comp.value, comp.description
-                            )?
+                        let value = zsh_quote(&comp.value);
+                        let mut display = sanitize(comp.display_text()).into_owned();
+                        if !comp.description.is_empty() {
+                            display += " -- ";
+                            display += &sanitize(&comp.description);
+                        }
+                        match &comp.suffix {
+                            None => writeln!(w, "\t{value}\t{display}")?,
+                            // The suffix is passed to `compadd -S`
+                            Some(suffix) => {
+                                writeln!(w, "\t{value}\t{display}\t{}", sanitize(suffix))?
+                            }
                         }
                     }
                 }
                 writeln!(w, "END")?;
+                if let Some(line) = directive_line {
+                    writeln!(w, "\t\t{line}")?;
+                }
             }
         }
         Ok(())
     }
+
+    /// The candidates to print, best first.
+    fn matched(&self, shell: Shell) -> Vec<&Completion> {
+        self.ranked()
+            .into_iter()
+            .map(|(comp, _)| comp)
+            .filter(|comp| {
+                // If there are multiple candates, bash will not complete :(
+                shell != Shell::Bash || self.strip_prefix(comp).starts_with(&self.arg)
+            })
+            .filter(|comp| !comp.value.is_empty()) // An empty value means a directive in line protocols
+            .collect()
+    }
+
+    /// The value without the prefix from [`Unready::to_ready`], which is what `arg` is compared to.
+    fn strip_prefix<'a>(&self, comp: &'a Completion) -> &'a str {
+        comp.value.strip_prefix(&self.prefix).unwrap_or(&comp.value)
+    }
+
+    fn match_comp(&self, comp: &Completion) -> Option<Match> {
+        let value = self.strip_prefix(comp);
+        let mut m = self.matcher.matches(value, &self.arg)?;
+        let offset = comp.value[..comp.value.len() - value.len()].chars().count();
+        m.positions.iter_mut().for_each(|p| *p += offset);
+        Some(m)
+    }
+
+    /// The candidates matching the value on command-line, with the [`Match`] results
+    /// from the matcher (see [`Ready::matcher`]), which carry the matched positions for highlighting.
+    /// They are ordered as [`Ready::order`] says, best first by default.
+    ///
+    /// Candidates with [`Completion::always_match`] are included even if they don't match,
+    /// in which case they come last (for [`Order::Score`]), without a [`Match`].
+    pub fn ranked(&self) -> Vec<(&Completion, Option<Match>)> {
+        let mut comps: Vec<_> = self
+            .comps
+            .iter()
+            .filter_map(|comp| match self.match_comp(comp) {
+                Some(m) => Some((comp, Some(m))),
+                None if comp.always_match => Some((comp, None)),
+                None => None,
+            })
+            .collect();
+        // NOTE: stable sort, so that candidates with the same score keep their order
+        match self.order {
+            Order::Score => {
+                comps.sort_by_key(|(_, m)| std::cmp::Reverse(m.as_ref().map(|m| m.score)))
+            }
+            Order::Insertion => (),
+            Order::Alphabetical => comps.sort_by(|(c1, _), (c2, _)| c1.value.cmp(&c2.value)),
+            Order::Priority => comps.sort_by(|(c1, _), (c2, _)| {
+                (c2.priority, &c1.value).cmp(&(c1.priority, &c2.value))
+            }),
+        }
+        comps
+    }
 }
 
 /// The object to represent an unready completion results.
@@ -169,6 +654,11 @@ impl Unready {
         self.preexist.extend(preexist);
         self
     }
+    /// Keep more of the typed word as is, e.g. `a,b,` in `a,b,c`, so that only the rest is completed.
+    pub(crate) fn extend_prefix(mut self, typed: &str) -> Self {
+        self.prefix += typed;
+        self
+    }
 
     /// Building a [`Ready`] completion based on an [`Unready`] one.
     /// You have to provide a vector of [`Completion`], which represents your custom completion logic.
@@ -192,21 +682,45 @@ impl Unready {
     /// For example, if you're completing a flag value `ls --color=<TAB>`,
     /// the vector provided here should be `[auto, never, always]`.
     /// But due to the nature of completion, the [`Ready`] object created by this function actually contains
-    /// `[--color=auto, --color=never, --color=always]`
+    /// `[--color=auto, --color=never, --color=always]`.
+    /// The menu still shows `[auto, never, always]` unless [`Completion::display`] is set explicitly.
     pub fn to_ready(self, comps: Vec<Completion>) -> Ready {
         log::info!("to_ready: {:?} with {:?}", self, comps);
         let mut final_comps = self.preexist;
         final_comps.extend(comps);
         if !self.prefix.is_empty() {
             for comp in final_comps.iter_mut() {
+                if comp.display.is_none() {
+                    comp.display = Some(comp.value.clone());
+                }
                 comp.set_value(|v| format!("{}{v}", self.prefix));
             }
         }
 
-        Ready {
-            arg: self.arg,
-            comps: final_comps,
-        }
+        // The candidates are matched against the word without the prefix
+        let arg = match self.arg.strip_prefix(&self.prefix) {
+            Some(arg) => arg.to_owned(),
+            None => self.arg,
+        };
+        let mut ready = Ready::new(final_comps, arg);
+        ready.prefix = self.prefix;
+        ready
+    }
+
+    /// Building a [`Ready`] completion which lets the shell complete with its native mechanism,
+    /// e.g. file paths. Shorthand for [`Unready::to_ready`] + [`Ready::directive`].
+    ///
+    /// ```no_run
+    /// use supplement::completion::{Directive, Ready, Unready};
+    /// # fn create_unready() -> Unready {
+    /// #     unimplemented!()
+    /// # }
+    ///
+    /// let unready: Unready = create_unready();
+    /// let ready: Ready = unready.to_directive(Directive::Glob("*.rs".to_owned()));
+    /// ```
+    pub fn to_directive(self, directive: Directive) -> Ready {
+        self.to_ready(vec![]).directive(directive)
     }
 }
 
@@ -253,22 +767,604 @@ pub enum CompletionGroup<ID> {
 }
 impl<ID> CompletionGroup<ID> {
     pub(crate) fn new_ready(comps: Vec<Completion>, arg: String) -> Self {
-        CompletionGroup::Ready(Ready { comps, arg })
+        CompletionGroup::Ready(Ready::new(comps, arg))
+    }
+}
+
+/// The description to show, falling back to the group name if there is none.
+fn desc_or_group(comp: &Completion) -> &str {
+    match (comp.description.as_str(), &comp.group) {
+        ("", Some(g)) => &g.name,
+        (desc, _) => desc,
+    }
+}
+
+/// Replace the characters that break line-based protocols with white spaces.
+fn sanitize(s: &str) -> Cow<'_, str> {
+    const CONTROL: &[char] = &['\t', '\n', '\r'];
+    if !s.contains(CONTROL) {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(s.replace(CONTROL, " "))
+}
+
+/// Bash inserts the value verbatim in place of the whole word, opening quote included.
+fn bash_quote(value: &str, quote: Quote) -> Cow<'_, str> {
+    const SPECIAL: &[char] = &[
+        ' ', '\t', '\n', '\r', '\'', '"', '\\', '$', '`', '|', '&', ';', '<', '>', '(', ')', '[',
+        ']', '{', '}', '#', '*', '?', '!', '~',
+    ];
+    // Control characters can't be in the line protocol, so write them in ANSI-C quoting
+    let ansi_c = |ch: char| match ch {
+        '\n' => Some("$'\\n'"),
+        '\t' => Some("$'\\t'"),
+        '\r' => Some("$'\\r'"),
+        _ => None,
+    };
+    let mut ret = String::new();
+    match quote {
+        Quote::None => {
+            if !value.contains(SPECIAL) {
+                return Cow::Borrowed(value);
+            }
+            for ch in value.chars() {
+                if let Some(s) = ansi_c(ch) {
+                    ret += s;
+                    continue;
+                }
+                if SPECIAL.contains(&ch) {
+                    ret.push('\\');
+                }
+                ret.push(ch);
+            }
+        }
+        Quote::Single => {
+            ret.push('\'');
+            for ch in value.chars() {
+                match (ch, ansi_c(ch)) {
+                    (_, Some(s)) => ret += &format!("'{s}'"),
+                    ('\'', _) => ret += "'\\''",
+                    (ch, _) => ret.push(ch),
+                }
+            }
+        }
+        Quote::Double => {
+            ret.push('"');
+            for ch in value.chars() {
+                match (ch, ansi_c(ch)) {
+                    (_, Some(s)) => ret += &format!("\"{s}\""),
+                    ('"' | '\\' | '$' | '`', _) => {
+                        ret.push('\\');
+                        ret.push(ch);
+                    }
+                    (ch, _) => ret.push(ch),
+                }
+            }
+        }
+    }
+    Cow::Owned(ret)
+}
+
+/// PowerShell replaces the whole word with the value, opening quote included.
+fn powershell_quote(value: &str, quote: Quote) -> Cow<'_, str> {
+    const SPECIAL: &[char] = &[
+        ' ', '\'', '"', '`', '$', '{', '}', '(', ')', '@', ';', ',', '|', '&', '<', '>', '#',
+    ];
+    const CONTROL: &[char] = &['\t', '\n', '\r'];
+    match quote {
+        Quote::None if !value.contains(SPECIAL) && !value.contains(CONTROL) => Cow::Borrowed(value),
+        Quote::None | Quote::Single if !value.contains(CONTROL) => {
+            Cow::Owned(format!("'{}'", value.replace('\'', "''")))
+        }
+        _ => {
+            let mut ret = String::from('"');
+            for ch in value.chars() {
+                match ch {
+                    '"' | '`' | '$' => {
+                        ret.push('`');
+                        ret.push(ch);
+                    }
+                    '\n' => ret += "`n",
+                    '\t' => ret += "`t",
+                    '\r' => ret += "`r",
+                    ch => ret.push(ch),
+                }
+            }
+            ret.push('"');
+            Cow::Owned(ret)
+        }
+    }
+}
+
+/// Nushell replaces the whole word with the value, opening quote included.
+fn nushell_quote(value: &str, quote: Quote) -> Cow<'_, str> {
+    const SPECIAL: &[char] = &[
+        ' ', '\t', '\n', '\r', '\'', '"', '`', '$', '|', ';', '(', ')', '[', ']', '{', '}', '#',
+    ];
+    match quote {
+        Quote::None if !value.contains(SPECIAL) => Cow::Borrowed(value),
+        Quote::None | Quote::Single if !value.contains(['\'', '\t', '\n', '\r']) => {
+            Cow::Owned(format!("'{value}'"))
+        }
+        _ => Cow::Owned(format!("\"{}\"", backslash_escape(value, '"'))),
+    }
+}
+
+/// Quote the value with single quotes if it can't be a bare word in xonsh's subprocess mode.
+/// If the user already opened a quote, xonsh inserts the value after it, so only escape the content.
+fn xonsh_quote(value: &str, quote: Quote) -> Cow<'_, str> {
+    const SPECIAL: &[char] = &[
+        ' ', '\t', '\n', '\'', '"', '\\', '$', '`', '|', '&', ';', '<', '>', '(', ')', '[', ']',
+        '{', '}', '#', '*', '?', '!', '@',
+    ];
+    match quote {
+        Quote::None if !value.contains(SPECIAL) => Cow::Borrowed(value),
+        Quote::None => Cow::Owned(format!("'{}'", backslash_escape(value, '\''))),
+        Quote::Single => Cow::Owned(backslash_escape(value, '\'')),
+        Quote::Double => Cow::Owned(backslash_escape(value, '"')),
+    }
+}
+
+/// The zsh script removes one level of quotes with `${(Q)...}` before `compadd`, which then quotes the
+/// value properly. So only the characters breaking the line protocol or `(Q)` need ANSI-C quoting.
+fn zsh_quote(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\t', '\n', '\r', '\\', '\'', '"']) {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(format!("$'{}'", backslash_escape(value, '\'')))
+}
+
+/// A regex matching the file names (not the whole paths) that the glob pattern matches, for Elvish.
+/// An empty glob matches everything.
+fn glob_to_regex(glob: &str) -> String {
+    let mut ret = String::from("(^|/)");
+    let mut in_bracket = false;
+    for ch in glob.chars() {
+        match ch {
+            '*' if !in_bracket => ret += "[^/]*",
+            '?' if !in_bracket => ret += "[^/]",
+            '[' if !in_bracket => {
+                in_bracket = true;
+                ret.push('[');
+            }
+            '!' if in_bracket && ret.ends_with('[') => ret.push('^'),
+            ']' if in_bracket => {
+                in_bracket = false;
+                ret.push(']');
+            }
+            '\\' | '^' | '$' | '.' | '|' | '+' | '(' | ')' | '{' | '}' | '[' => {
+                ret.push('\\');
+                ret.push(ch);
+            }
+            ch => ret.push(ch),
+        }
+    }
+    if glob.is_empty() {
+        ret += "[^/]*";
+    }
+    ret.push('$');
+    ret
+}
+
+/// Escape `\`, `quote` and control characters with backslash, like in C or Python.
+fn backslash_escape(value: &str, quote: char) -> String {
+    let mut ret = String::new();
+    for ch in value.chars() {
+        match ch {
+            '\\' => ret += "\\\\",
+            '\n' => ret += "\\n",
+            '\t' => ret += "\\t",
+            '\r' => ret += "\\r",
+            ch if ch == quote => {
+                ret.push('\\');
+                ret.push(ch);
+            }
+            ch => ret.push(ch),
+        }
+    }
+    ret
+}
+
+enum Json<'a> {
+    Str(&'a str),
+    Bool(bool),
+}
+
+fn write_json_record(w: &mut impl Write, fields: &[(&str, Json<'_>)]) -> IoResult<()> {
+    write!(w, "{{")?;
+    for (i, (key, value)) in fields.iter().enumerate() {
+        if i != 0 {
+            write!(w, ",")?;
+        }
+        write_json_str(w, key)?;
+        write!(w, ":")?;
+        match value {
+            Json::Str(s) => write_json_str(w, s)?,
+            Json::Bool(b) => write!(w, "{b}")?,
+        }
+    }
+    write!(w, "}}")
+}
+
+fn write_json_str(w: &mut impl Write, s: &str) -> IoResult<()> {
+    write!(w, "\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' => write!(w, "\\\"")?,
+            '\\' => write!(w, "\\\\")?,
+            '\n' => write!(w, "\\n")?,
+            '\r' => write!(w, "\\r")?,
+            '\t' => write!(w, "\\t")?,
+            ch if ch.is_control() => write!(w, "\\u{:04x}", ch as u32)?,
+            ch => write!(w, "{ch}")?,
+        }
     }
+    write!(w, "\"")
 }
 
-fn naive_fuzzy(mut value: &str, pattern: &str) -> bool {
-    if pattern.len() > value.len() {
-        return false;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn print(comps: Vec<Completion>, arg: &str, shell: Shell) -> String {
+        let ready = Ready::new(comps, arg.to_owned());
+        let mut out = vec![];
+        ready.print(shell, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_print_json() {
+        let comps = vec![
+            Completion::new("master", "the \"main\" branch").group("branch"),
+            Completion::new("HEAD", "").always_match(),
+        ];
+        let ready = Ready::new(comps, "ma".to_owned());
+        let mut out = vec![];
+        ready.print_json(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"{"arg":"ma","directive":null,"completions":[{"value":"master","display":"master","description":"the \"main\" branch","group":"branch","always_match":false,"priority":0,"suffix":null,"score":56,"positions":[0,1]},{"value":"HEAD","display":"HEAD","description":"","group":null,"always_match":true,"priority":0,"suffix":null,"score":null,"positions":null}]}"#
+                .to_owned()
+                + "\n"
+        );
+    }
+
+    #[test]
+    fn test_print_powershell() {
+        let comps = vec![
+            Completion::new("checkout", "switch branches").group("command"),
+            Completion::new("log", "").group("command"),
+            Completion::new("--graph", ""),
+            Completion::new("other", ""),
+        ];
+        assert_eq!(
+            print(comps, "o", Shell::PowerShell),
+            "other\tother\tother\ncheckout\tcheckout\tswitch branches\nlog\tlog\tcommand\n"
+        );
+    }
+
+    #[test]
+    fn test_print_nushell() {
+        let comps = vec![
+            Completion::new("checkout", "switch \"branches\"").group("command"),
+            Completion::new("log", "").group("command"),
+            Completion::new("other", "a\tb"),
+        ];
+        assert_eq!(
+            print(comps, "o", Shell::Nushell),
+            r#"[{"value":"other","description":"a\tb"},{"value":"checkout","description":"command: switch \"branches\""},{"value":"log","description":"command"}]"#
+                .to_owned()
+                + "\n"
+        );
+        assert_eq!(print(vec![], "", Shell::Nushell), "[]\n");
+    }
+
+    #[test]
+    fn test_print_elvish() {
+        let comps = vec![
+            Completion::new("checkout", "").group("command"),
+            Completion::new("my file", "with 'quote'"),
+        ];
+        assert_eq!(
+            print(comps, "", Shell::Elvish),
+            "{\"value\":\"checkout\",\"display\":\"checkout\",\"description\":\"command\",\"suffix\":\" \"}\n\
+             {\"value\":\"my file\",\"display\":\"my file\",\"description\":\"with 'quote'\",\"suffix\":\" \"}\n"
+        );
     }
 
-    for ch in pattern.chars() {
-        let pos = value.chars().position(|t| t == ch);
-        let Some(pos) = pos else {
-            return false;
+    #[test]
+    fn test_group() {
+        let remote = "origin".to_owned();
+        let comps = vec![
+            Completion::new("main", "").group(remote.clone()),
+            Completion::new("dev", "").group(remote),
+            Completion::new("log", "").group("command"),
+            Completion::new("tag", "").group(Group::new("ref").description("refs\tand tags")),
+            Completion::new("HEAD", "").group(Group::new("special").priority(1)),
+        ];
+        assert_eq!(
+            print(comps.clone(), "", Shell::Zsh),
+            "special\n\tHEAD\tHEAD\n\
+             command\n\tlog\tlog\n\
+             ref\trefs and tags\n\ttag\ttag\n\
+             origin\n\tmain\tmain\n\tdev\tdev\n\
+             END\n"
+        );
+        assert_eq!(
+            print(comps, "", Shell::Fish),
+            "main\torigin\ndev\torigin\nlog\tcommand\ntag\tref\nHEAD\tspecial\n"
+        );
+    }
+
+    #[test]
+    fn test_order() {
+        let values = |order| {
+            let comps = vec![
+                Completion::new("restore", ""),
+                Completion::new("rebase", "").priority(1),
+                Completion::new("reset", ""),
+                Completion::new("HEAD", "").always_match(),
+            ];
+            let ready = Ready::new(comps, "res".to_owned()).order(order);
+            let ranked = ready.ranked();
+            ranked
+                .iter()
+                .map(|(c, _)| c.value.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(Order::Score), ["restore", "reset", "rebase", "HEAD"]);
+        assert_eq!(
+            values(Order::Insertion),
+            ["restore", "rebase", "reset", "HEAD"]
+        );
+        assert_eq!(
+            values(Order::Alphabetical),
+            ["HEAD", "rebase", "reset", "restore"]
+        );
+        assert_eq!(
+            values(Order::Priority),
+            ["rebase", "HEAD", "reset", "restore"]
+        );
+
+        let print = |order, shell| {
+            let comps = vec![
+                Completion::new("b", "").group("x"),
+                Completion::new("a", "").group("y"),
+                Completion::new("c", "").group("y"),
+            ];
+            let ready = Ready::new(comps, String::new()).order(order);
+            let mut w = vec![];
+            ready.print(shell, &mut w).unwrap();
+            String::from_utf8(w).unwrap()
+        };
+        assert_eq!(print(Order::Score, Shell::Bash), "b\na\nc\n");
+        assert_eq!(
+            print(Order::Insertion, Shell::Bash),
+            "b\na\nc\n\tnosort\t\t\t\n"
+        );
+        assert_eq!(
+            print(Order::Score, Shell::Zsh),
+            "x\n\tb\tb\ny\n\ta\ta\n\tc\tc\nEND\n"
+        );
+        assert_eq!(
+            print(Order::Insertion, Shell::Zsh),
+            "x\t\tunsorted\n\tb\tb\ny\t\tunsorted\n\ta\ta\n\tc\tc\nEND\n"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let ready = || {
+            Unready::new("--color=".to_owned(), "al".to_owned()).to_ready(vec![
+                Completion::new("always", ""),
+                Completion::new("auto", "").display("auto (default)"),
+            ])
+        };
+        let print = |shell| {
+            let mut w = vec![];
+            ready().print(shell, &mut w).unwrap();
+            String::from_utf8(w).unwrap()
+        };
+        let comps = &ready().comps;
+        assert_eq!(comps[0].value, "--color=always");
+        assert_eq!(comps[0].display_text(), "always");
+        assert_eq!(comps[1].display_text(), "auto (default)");
+
+        assert_eq!(print(Shell::Zsh), "option\n\t--color=always\talways\nEND\n");
+        assert_eq!(print(Shell::PowerShell), "--color=always\talways\talways\n");
+        assert_eq!(
+            print(Shell::Nushell),
+            r#"[{"value":"--color=always","description":"","display_override":"always"}]"#
+                .to_owned()
+                + "\n"
+        );
+        // Bash and fish can only show the value
+        assert_eq!(print(Shell::Fish), "--color=always\t\n");
+    }
+
+    #[test]
+    fn test_suffix() {
+        let comps = || {
+            vec![
+                Completion::new("--color=", "").no_space(),
+                Completion::new("src", "").suffix("/"),
+            ]
+        };
+        assert_eq!(
+            print(comps(), "", Shell::Bash),
+            "--color=\nsrc/\n\tnospace\t\t\t\n"
+        );
+        assert_eq!(print(comps(), "", Shell::Fish), "--color=\t\nsrc/\t\n");
+        assert_eq!(
+            print(comps(), "", Shell::Zsh),
+            "option\n\t--color=\t--color=\t\n\tsrc\tsrc\t/\nEND\n"
+        );
+        assert_eq!(
+            print(comps(), "", Shell::Nushell),
+            r#"[{"value":"--color=","description":"","append_whitespace":false},{"value":"src/","description":"","append_whitespace":false}]"#
+                .to_owned()
+                + "\n"
+        );
+        assert_eq!(
+            print(comps(), "", Shell::Elvish),
+            "{\"value\":\"--color=\",\"display\":\"--color=\",\"description\":\"\",\"suffix\":\"\"}\n\
+             {\"value\":\"src\",\"display\":\"src\",\"description\":\"\",\"suffix\":\"/\"}\n"
+        );
+        assert_eq!(
+            print(comps(), "", Shell::Xonsh),
+            r#"[{"value":"--color=","display":"--color=","description":"","append_space":false},{"value":"src/","display":"src","description":"","append_space":false}]"#
+                .to_owned()
+                + "\n"
+        );
+        // Without any suffix, bash keeps the trailing space
+        let comps = vec![Completion::new("src", "")];
+        assert_eq!(print(comps, "", Shell::Bash), "src\n");
+    }
+
+    #[test]
+    fn test_print_xonsh() {
+        let comps = vec![
+            Completion::new("checkout", "").group("command"),
+            Completion::new("it's mine", ""),
+        ];
+        assert_eq!(
+            print(comps, "", Shell::Xonsh),
+            r#"[{"value":"checkout","display":"checkout","description":"command","append_space":true},{"value":"'it\\'s mine'","display":"it's mine","description":"","append_space":true}]"#
+                .to_owned()
+                + "\n"
+        );
+    }
+
+    #[test]
+    fn test_print_escape() {
+        let comps = || {
+            vec![
+                Completion::new("my file", "multi\nline"),
+                Completion::new("it's", "a\tb"),
+                Completion::new("plain", ""),
+            ]
         };
-        value = &value[pos + 1..];
+        let print_quote = |shell: Shell, quote: Quote| {
+            let ready = Ready::new(comps(), String::new());
+            let mut out = vec![];
+            ready.quote(quote).print(shell, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            print_quote(Shell::Bash, Quote::None),
+            "my\\ file\nit\\'s\nplain\n"
+        );
+        assert_eq!(
+            print_quote(Shell::Bash, Quote::Single),
+            "'my file\n'it'\\''s\n'plain\n"
+        );
+        assert_eq!(
+            print_quote(Shell::Bash, Quote::Double),
+            "\"my file\n\"it's\n\"plain\n"
+        );
+        assert_eq!(bash_quote("a\nb", Quote::Double), r#""a"$'\n'"b"#);
+        assert_eq!(
+            print_quote(Shell::Fish, Quote::None),
+            "my file\tmulti line\nit's\ta b\nplain\t\n"
+        );
+        assert_eq!(
+            print_quote(Shell::Zsh, Quote::None),
+            "option\n\tmy file\tmy file -- multi line\n\t$'it\\'s'\tit's -- a b\n\tplain\tplain\nEND\n"
+        );
+        assert_eq!(
+            print_quote(Shell::PowerShell, Quote::None),
+            "'my file'\tmy file\tmulti line\n'it''s'\tit's\ta b\nplain\tplain\tplain\n"
+        );
+        assert_eq!(
+            powershell_quote("say \"$hi\"\n", Quote::Double),
+            "\"say `\"`$hi`\"`n\""
+        );
+        assert_eq!(nushell_quote("my file", Quote::None), "'my file'");
+        assert_eq!(nushell_quote("it's", Quote::Single), r#""it's""#);
+        assert_eq!(xonsh_quote("it's", Quote::Single), r"it\'s");
+        assert_eq!(xonsh_quote("it's", Quote::Double), "it's");
     }
 
-    true
+    #[test]
+    fn test_matcher() {
+        use crate::matcher::{Prefix, Substring};
+
+        let comps = || {
+            vec![
+                Completion::new("homes", ""),
+                Completion::new("merge", ""),
+                Completion::new("HEAD", "").always_match(),
+                Completion::new("main", ""),
+            ]
+        };
+        let print_with = |shell: Shell, matcher: &dyn Fn(Ready) -> Ready| {
+            let ready = matcher(Ready::new(comps(), "me".to_owned()));
+            let mut out = vec![];
+            ready.print(shell, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        // Fuzzy by default, and the compact ones first
+        assert_eq!(
+            print_with(Shell::Fish, &|r| r),
+            "merge\t\nhomes\t\nHEAD\t\n"
+        );
+        assert_eq!(
+            print_with(Shell::Fish, &|r| r.matcher(Prefix)),
+            "merge\t\nHEAD\t\n"
+        );
+        assert_eq!(
+            print_with(Shell::Fish, &|r| r.matcher(Substring)),
+            "merge\t\nhomes\t\nHEAD\t\n"
+        );
+        assert_eq!(
+            print_with(Shell::Bash, &|r| r.matcher(Substring)),
+            "merge\n"
+        );
+    }
+
+    #[test]
+    fn test_directive() {
+        let print_directive = |shell: Shell, comps: Vec<Completion>| {
+            let mut unready = Unready::new("--opt=".to_owned(), "src/ma".to_owned());
+            unready.preexist = comps;
+            let ready = unready.to_directive(Directive::Glob("*.rs".to_owned()));
+            let mut out = vec![];
+            ready.print(shell, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let comps = || vec![Completion::new("src/main x", "")];
+
+        // Bash quotes the values itself with `-o filenames`
+        assert_eq!(
+            print_directive(Shell::Bash, comps()),
+            "--opt=src/main x\n\tglob\t--opt=\tsrc/ma\t*.rs\n"
+        );
+        assert_eq!(
+            print_directive(Shell::Fish, comps()),
+            "--opt=src/main x\t\n\tglob\t--opt=\tsrc/ma\t*.rs\n"
+        );
+        assert_eq!(
+            print_directive(Shell::Zsh, vec![]),
+            "END\n\t\tglob\t--opt=\tsrc/ma\t*.rs\n"
+        );
+        assert_eq!(
+            print_directive(Shell::Elvish, vec![]),
+            "{\"directive\":\"glob\",\"prefix\":\"--opt=\",\"arg\":\"src/ma\",\"regex\":\"(^|/)[^/]*\\\\.rs$\"}\n"
+        );
+        assert_eq!(print_directive(Shell::Nushell, vec![]), "null\n");
+        assert!(print_directive(Shell::Xonsh, comps()).starts_with('['));
+
+        let mut out = vec![];
+        Ready::new(vec![], "".to_owned())
+            .directive(Directive::Nothing)
+            .print(Shell::Nushell, &mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "[]\n");
+
+        assert_eq!(glob_to_regex("[!a]?.(x)"), r"(^|/)[^a][^/]\.\(x\)$");
+        assert_eq!(glob_to_regex(""), "(^|/)[^/]*$");
+    }
 }
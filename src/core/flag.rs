@@ -1,10 +1,12 @@
-use super::{CowOwned, CowSlice, CowStr, PossibleValues, comp_with_possible, parse_flag};
+use super::{
+    ArgIter, CowSlice, CowStr, Hidden, Names, PossibleValues, Required, comp_with_possible,
+    parse_flag, split_values,
+};
 use crate::completion::{CompletionGroup, Unready};
 use crate::error::Error;
 use crate::parsed_flag::ParsedFlag;
 use crate::{Completion, Result, Seen, id};
 use std::fmt::Debug;
-use std::iter::Peekable;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum CompleteWithEqual {
@@ -27,10 +29,20 @@ pub mod flag_type {
     #[derive(Clone, Copy, Debug)]
     pub struct Bool {
         pub(crate) seen_id: id::NoVal,
+        /// The other flag of a negation pair, turned off by this one, e.g. `--color` and `--no-color`.
+        pub(crate) opposite: Option<id::NoVal>,
+        /// Whether this is the negative one, e.g. `--no-color`.
+        pub(crate) negate: bool,
     }
     impl Bool {
         pub(crate) fn push(&self, seen: &mut Seen) {
-            seen.push_no_val(self.seen_id)
+            seen.push_no_val(self.seen_id);
+            match self.opposite {
+                // The positive one is disabled even if it's never seen
+                Some(opposite) if self.negate => seen.push_negated(opposite),
+                Some(opposite) => seen.turn_off(opposite),
+                None => (),
+            }
         }
     }
     #[doc(hidden)]
@@ -40,10 +52,17 @@ pub mod flag_type {
         pub(crate) seen_id: id::Valued,
         pub(crate) complete_with_equal: CompleteWithEqual,
         pub(crate) possible_values: PossibleValues,
+        /// How many values are taken after the flag, e.g. `--point X Y`.
+        /// NOTE: it doesn't apply to values after an equal sign.
+        pub(crate) min_values: usize,
+        pub(crate) max_values: usize,
+        pub(crate) value_delimiter: Option<char>,
     }
     impl<ID> Valued<ID> {
         pub(crate) fn push(&self, seen: &mut Seen, arg: String) {
-            seen.push_valued(self.seen_id, arg)
+            for value in split_values(arg, self.value_delimiter) {
+                seen.push_valued(self.seen_id, value)
+            }
         }
     }
 
@@ -54,19 +73,64 @@ pub mod flag_type {
     }
     impl<ID> Type<ID> {
         pub const fn new_bool(seen_id: id::NoVal) -> Self {
-            Type::Bool(Bool { seen_id })
+            Type::Bool(Bool {
+                seen_id,
+                opposite: None,
+                negate: false,
+            })
+        }
+        /// A boolean flag which can be turned off by the flag with `negation`, e.g. `--color` for `--no-color`.
+        pub const fn new_negatable(seen_id: id::NoVal, negation: id::NoVal) -> Self {
+            Type::Bool(Bool {
+                seen_id,
+                opposite: Some(negation),
+                negate: false,
+            })
+        }
+        /// The negation of the boolean flag with `positive`, e.g. `--no-color` for `--color`.
+        /// Put `once: false` on both flags if the later one can override the earlier.
+        pub const fn new_negation(seen_id: id::NoVal, positive: id::NoVal) -> Self {
+            Type::Bool(Bool {
+                seen_id,
+                opposite: Some(positive),
+                negate: true,
+            })
         }
         pub const fn new_valued(
             id: Option<ID>,
             seen_id: id::Valued,
             complete_with_equal: CompleteWithEqual,
             possible_values: PossibleValues,
+        ) -> Self {
+            Self::new_multi_valued(
+                id,
+                seen_id,
+                complete_with_equal,
+                possible_values,
+                1,
+                1,
+                None,
+            )
+        }
+        /// A valued flag taking `min_values..=max_values` values, e.g. clap's `num_args = 1..`,
+        /// and each of them may be split by `value_delimiter`, e.g. clap's `value_delimiter = ','`.
+        pub const fn new_multi_valued(
+            id: Option<ID>,
+            seen_id: id::Valued,
+            complete_with_equal: CompleteWithEqual,
+            possible_values: PossibleValues,
+            min_values: usize,
+            max_values: usize,
+            value_delimiter: Option<char>,
         ) -> Self {
             Type::Valued(Valued {
                 id,
                 seen_id,
                 complete_with_equal,
                 possible_values,
+                min_values,
+                max_values,
+                value_delimiter,
             })
         }
     }
@@ -74,17 +138,6 @@ pub mod flag_type {
 
 use flag_type::*;
 
-type Longs = CowOwned<&'static str, String>;
-impl Longs {
-    pub fn iter(&self) -> impl Iterator<Item = &str> {
-        let (v1, v2): (&[&str], &[String]) = match self {
-            CowOwned::Borrow(v) => (*v, &[]),
-            CowOwned::Owned(v) => (&[], v.as_slice()),
-        };
-        v1.iter().copied().chain(v2.iter().map(|s| s.as_str()))
-    }
-}
-
 /// The object to represent a CLI flag.
 ///
 /// NOTE: this includes boolean flags (e.g. `ls -l`) **AND** valued flags (e.g. `ls --color`).
@@ -95,9 +148,17 @@ impl Longs {
 pub struct Flag<ID> {
     pub ty: Type<ID>,
     pub short: CowSlice<char>,
-    pub long: Longs,
+    pub long: Names,
+    /// Only used in parsing and never completed, i.e. clap's hidden aliases.
+    pub hidden_short: CowSlice<char>,
+    pub hidden_long: Names,
+    pub hidden: Hidden,
     pub description: CowStr,
     pub once: bool,
+    /// Not available once any of these is seen, i.e. clap's `conflicts_with` and exclusive `ArgGroup`.
+    pub conflicts: CowSlice<id::Any>,
+    /// Required if any of these holds.
+    pub required: CowSlice<Required>,
 }
 
 impl<ID: PartialEq + Copy + Debug> Flag<ID> {
@@ -137,10 +198,12 @@ impl<ID: PartialEq + Copy + Debug> Flag<ID> {
                     CompleteWithEqual::NoNeed => (),
                     CompleteWithEqual::Must => {
                         comp.value += "=";
+                        comp = comp.no_space();
                     }
                     CompleteWithEqual::Optional => {
                         more = Some(comp.clone());
                         comp.value += "=";
+                        comp = comp.no_space();
                     }
                 }
             }
@@ -148,9 +211,24 @@ impl<ID: PartialEq + Copy + Debug> Flag<ID> {
         })
     }
 
+    pub(super) fn is_missing(&self, seen: &Seen) -> bool {
+        let required = self.required.iter().any(|r| r.holds(seen));
+        required && !self.exists_in_seen(seen)
+    }
+
+    pub(super) fn conflicts_in_seen(&self, seen: &Seen) -> bool {
+        self.conflicts.iter().any(|id| seen.contains(*id))
+    }
+
+    /// For a negation pair, seeing either flag counts, since only one of them can be on.
     pub(super) fn exists_in_seen(&self, seen: &Seen) -> bool {
         match self.ty {
-            Type::Bool(Bool { seen_id, .. }) => seen.find(seen_id).is_some(),
+            Type::Bool(Bool {
+                seen_id, opposite, ..
+            }) => {
+                let mut ids = std::iter::once(seen_id).chain(opposite);
+                ids.any(|id| seen.find(id).is_some())
+            }
             Type::Valued(Valued {
                 seen_id: id::Valued::Single(id),
                 ..
@@ -165,7 +243,7 @@ impl<ID: PartialEq + Copy + Debug> Flag<ID> {
     pub(super) fn supplement(
         &self,
         seen: &mut Seen,
-        args: &mut Peekable<impl Iterator<Item = String>>,
+        args: &mut ArgIter<impl Iterator<Item = String>>,
     ) -> Result<Option<CompletionGroup<ID>>> {
         let valued = match &self.ty {
             Type::Bool(inner) => {
@@ -190,28 +268,62 @@ impl<ID: PartialEq + Copy + Debug> Flag<ID> {
             }
         }
 
-        let arg = args.next().unwrap();
-        match parse_flag(&arg, false) {
-            ParsedFlag::NotFlag | ParsedFlag::Empty | ParsedFlag::SingleDash => (),
-            ParsedFlag::DoubleDash | ParsedFlag::Long { .. } | ParsedFlag::Shorts => {
+        // Keep taking values until the count is satisfied, or a flag appears.
+        // Once the minimum is taken, anything like a flag ends the values, e.g. `--files a -<TAB>` completes flags.
+        let mut count = 0;
+        while count < valued.max_values {
+            if count >= valued.min_values {
+                match args.peek() {
+                    Some(arg) if !arg.starts_with('-') => (),
+                    _ => break,
+                }
+            }
+
+            let Some(arg) = args.next() else {
+                // Only possible after the cursor, e.g. `cmd | --flag`
+                return Err(Error::FlagNoValue(name.to_owned()));
+            };
+            if is_flag(&arg) {
                 log::warn!(
                     "`--{name} {arg}` is invalid. Maybe you should write it like `--{name}={arg}",
                 );
                 return Err(Error::FlagNoValue(name.to_owned()));
             }
-        }
 
-        if args.peek().is_none() {
-            let unready = Unready::new(String::new(), arg.clone());
-            let group = comp_with_possible(unready, &valued.possible_values, arg, valued.id);
-            return Ok(Some(group));
-        }
+            if args.at_cursor() {
+                let unready = Unready::new(String::new(), arg.clone());
+                let group = comp_with_possible(
+                    unready,
+                    &valued.possible_values,
+                    arg,
+                    valued.id,
+                    valued.value_delimiter,
+                );
+                return Ok(Some(group));
+            }
 
-        valued.push(seen, arg);
+            valued.push(seen, arg);
+            count += 1;
+        }
+        if count == 0 {
+            log::info!("flag {name} doesn't have value. Push an empty string like optional flags.");
+            valued.push(seen, String::new());
+        }
         Ok(None)
     }
 
+    pub(super) fn all_longs(&self) -> impl Iterator<Item = &str> {
+        self.long.iter().chain(self.hidden_long.iter())
+    }
+
     pub(crate) fn name(&self) -> &str {
         self.long.iter().next().unwrap_or_default()
     }
 }
+
+fn is_flag(arg: &str) -> bool {
+    match parse_flag(arg, false) {
+        ParsedFlag::NotFlag | ParsedFlag::Empty | ParsedFlag::SingleDash => false,
+        ParsedFlag::DoubleDash | ParsedFlag::Long { .. } | ParsedFlag::Shorts => true,
+    }
+}
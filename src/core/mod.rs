@@ -9,7 +9,7 @@ pub use crate::id;
 pub use flag::{CompleteWithEqual, Flag, flag_type};
 
 use crate::arg_context::ArgsContext;
-use crate::completion::{CompletionGroup, Unready};
+use crate::completion::{CompletionGroup, Order, Ready, Unready};
 use crate::error::Error;
 use crate::parsed_flag::ParsedFlag;
 use crate::{Completion, Result, Seen};
@@ -41,6 +41,17 @@ pub enum CowOwned<T: 'static, U: 'static> {
     Owned(Vec<U>),
 }
 
+type Names = CowOwned<&'static str, String>;
+impl Names {
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let (v1, v2): (&[&str], &[String]) = match self {
+            CowOwned::Borrow(v) => (*v, &[]),
+            CowOwned::Owned(v) => (&[], v.as_slice()),
+        };
+        v1.iter().copied().chain(v2.iter().map(|s| s.as_str()))
+    }
+}
+
 type StringPair = (String, String);
 type PossibleValues = CowOwned<(&'static str, &'static str), StringPair>;
 impl PossibleValues {
@@ -60,16 +71,91 @@ pub struct Arg<ID> {
     pub id: Option<ID>,
     pub seen_id: id::Valued,
     pub max_values: usize,
+    /// Only values after `--` go to this arg, i.e. clap's `last(true)`.
+    pub last: bool,
+    /// Split a value into many, e.g. `a,b,c` with clap's `value_delimiter = ','`.
+    pub value_delimiter: Option<char>,
     pub possible_values: PossibleValues,
+    /// Not available once any of these is seen, i.e. clap's `conflicts_with` and exclusive `ArgGroup`.
+    pub conflicts: CowSlice<id::Any>,
+    /// Required if any of these holds.
+    pub required: CowSlice<Required>,
+}
+impl<ID> Arg<ID> {
+    fn is_missing(&self, seen: &Seen) -> bool {
+        let required = self.required.iter().any(|r| r.holds(seen));
+        required && !seen.contains(self.seen_id.any())
+    }
+}
+
+/// When a flag or an arg is required.
+#[derive(Debug)]
+pub enum Required {
+    /// i.e. clap's `required`
+    Always,
+    /// Unless any of these is seen, i.e. clap's `required_unless_present_any`.
+    UnlessPresent(CowSlice<id::Any>),
+}
+impl Required {
+    pub(crate) fn holds(&self, seen: &Seen) -> bool {
+        match self {
+            Required::Always => true,
+            Required::UnlessPresent(ids) => !ids.iter().any(|id| seen.contains(*id)),
+        }
+    }
+}
+
+/// How a hidden flag or command is completed, e.g. with clap's `hide(true)`.
+/// It's always parsed anyway.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Hidden {
+    /// Not hidden at all.
+    #[default]
+    No,
+    /// Only completed if the word typed so far is a prefix of its name, e.g. `--debug` for `--deb<TAB>`.
+    Prefixed,
+    /// Never completed.
+    Always,
+}
+impl Hidden {
+    fn is_completed<'a>(self, mut names: impl Iterator<Item = &'a str>, typed: &str) -> bool {
+        match self {
+            Hidden::No => true,
+            Hidden::Prefixed => !typed.is_empty() && names.any(|n| n.starts_with(typed)),
+            Hidden::Always => false,
+        }
+    }
+}
+
+/// Split a value into many if there is a delimiter.
+fn split_values(value: String, delimiter: Option<char>) -> Vec<String> {
+    match delimiter {
+        Some(d) => value.split(d).map(|s| s.to_owned()).collect(),
+        None => vec![value],
+    }
 }
 
 fn comp_with_possible<ID>(
     mut unready: Unready,
     values: &PossibleValues,
-    value: String,
+    mut value: String,
     id: Option<ID>,
+    delimiter: Option<char>,
 ) -> CompletionGroup<ID> {
-    let values = values.iter().map(|(v, d)| Completion::new(v, d));
+    // E.g. `a,b,c` with delimiter `,`. Only complete the last segment, and skip what's already in the list.
+    let mut used = vec![];
+    if let Some(d) = delimiter
+        && let Some(pos) = value.rfind(d)
+    {
+        let last = value.split_off(pos + d.len_utf8());
+        unready = unready.extend_prefix(&value);
+        used = split_values(value, delimiter);
+        value = last;
+    }
+    let values = values
+        .iter()
+        .filter(|(v, _)| !used.iter().any(|u| u == v))
+        .map(|(v, d)| Completion::new(v, d));
     unready = unready.preexist(values);
     match id {
         Some(id) => CompletionGroup::Unready { id, unready, value },
@@ -83,9 +169,16 @@ fn comp_with_possible<ID>(
 pub struct Command<ID: 'static> {
     pub name: CowStr,
     pub description: CowStr,
+    /// Other names only used in parsing and never completed, e.g. `co` for `checkout`.
+    pub aliases: Names,
+    pub hidden: Hidden,
     pub all_flags: CowSlice<Flag<ID>>,
     pub args: CowSlice<Arg<ID>>,
     pub commands: CowSlice<Command<ID>>,
+    /// Accept unique prefixes of long flags, e.g. `--gr` for `--graph`. See clap's `infer_long_args`.
+    pub infer_long_args: bool,
+    /// Accept unique prefixes of subcommands, e.g. `lo` for `log`. See clap's `infer_subcommands`.
+    pub infer_subcommands: bool,
 }
 
 fn supplement_arg<ID: PartialEq + Copy + Debug>(
@@ -96,7 +189,9 @@ fn supplement_arg<ID: PartialEq + Copy + Debug>(
     let Some(arg_obj) = ctx.next_arg() else {
         return Err(Error::UnexpectedArg(arg));
     };
-    seen.push_valued(arg_obj.seen_id, arg);
+    for value in split_values(arg, arg_obj.value_delimiter) {
+        seen.push_valued(arg_obj.seen_id, value);
+    }
     Ok(())
 }
 fn parse_flag(s: &str, disable_flag: bool) -> ParsedFlag<'_> {
@@ -112,7 +207,13 @@ fn check_no_flag<ID>(arg: String, v: Vec<Completion>) -> Result<CompletionGroup<
     if v.is_empty() {
         return Err(Error::UnexpectedFlag);
     }
-    Ok(CompletionGroup::new_ready(v, arg))
+    // Show the required flags first
+    let has_required = v.iter().any(|c| c.priority > 0);
+    let mut ready = Ready::new(v, arg);
+    if has_required {
+        ready = ready.order(Order::Priority);
+    }
+    Ok(CompletionGroup::Ready(ready))
 }
 
 impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
@@ -128,9 +229,13 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
     ///     Command {
     ///         name: Cow::Borrowed(name),
     ///         description: Cow::Borrowed(""),
+    ///         aliases: CowOwned::Borrow(&[]),
+    ///         hidden: Hidden::No,
     ///         all_flags: CowSlice::Borrow(&[]),
     ///         args: CowSlice::Borrow(&[]),
     ///         commands: CowSlice::Borrow(subcmd),
+    ///         infer_long_args: false,
+    ///         infer_subcommands: false,
     ///     }
     /// }
     ///
@@ -161,24 +266,125 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
     pub fn supplement_with_seen(
         &self,
         seen: &mut Seen,
-        mut args: impl Iterator<Item = String>,
+        args: impl Iterator<Item = String>,
+    ) -> Result<CompletionGroup<ID>> {
+        self.supplement_with_seen_inner(seen, ArgIter::new(args, None))
+    }
+
+    /// Similar to [`Command::supplement`], but the word to complete is not necessarily the last one.
+    /// `cursor` is the index of the word under cursor, where the program's name is at index `0`.
+    ///
+    /// Words after the cursor are also parsed into [`Seen`], as long as they make sense.
+    /// Note that the completion itself only depends on the words before the cursor.
+    /// e.g. for `git log | --graph`, the args should be `["git", "log", "", "--graph"]` with `cursor` being `2`.
+    ///
+    /// ```
+    /// # use supplement::core::*;
+    /// # use supplement::*;
+    /// # use supplement::completion::CompletionGroup;
+    /// # use std::borrow::Cow;
+    /// # type ID = u32;
+    /// const GRAPH_ID: id::NoVal = id::NoVal::new(0);
+    /// const GRAPH: Flag<ID> = Flag {
+    ///     ty: flag_type::Type::new_bool(GRAPH_ID),
+    ///     short: CowSlice::Borrow(&[]),
+    ///     long: CowOwned::Borrow(&["graph"]),
+    ///     hidden_short: CowSlice::Borrow(&[]),
+    ///     hidden_long: CowOwned::Borrow(&[]),
+    ///     hidden: Hidden::No,
+    ///     description: Cow::Borrowed(""),
+    ///     once: true,
+    ///     conflicts: CowSlice::Borrow(&[]),
+    ///     required: CowSlice::Borrow(&[]),
+    /// };
+    /// const COLOR: Flag<ID> = Flag {
+    ///     ty: flag_type::Type::new_bool(id::NoVal::new(1)),
+    ///     short: CowSlice::Borrow(&[]),
+    ///     long: CowOwned::Borrow(&["color"]),
+    ///     hidden_short: CowSlice::Borrow(&[]),
+    ///     hidden_long: CowOwned::Borrow(&[]),
+    ///     hidden: Hidden::No,
+    ///     description: Cow::Borrowed(""),
+    ///     once: true,
+    ///     conflicts: CowSlice::Borrow(&[]),
+    ///     required: CowSlice::Borrow(&[]),
+    /// };
+    /// const LOG: Command<ID> = Command {
+    ///     name: Cow::Borrowed("log"),
+    ///     description: Cow::Borrowed(""),
+    ///     aliases: CowOwned::Borrow(&[]),
+    ///     hidden: Hidden::No,
+    ///     all_flags: CowSlice::Borrow(&[GRAPH, COLOR]),
+    ///     args: CowSlice::Borrow(&[]),
+    ///     commands: CowSlice::Borrow(&[]),
+    ///     infer_long_args: false,
+    ///     infer_subcommands: false,
+    /// };
+    ///
+    /// // `log --| --graph`
+    /// let args = ["log", "--", "--graph"].iter().map(|s| s.to_string());
+    /// let (seen, grp) = LOG.supplement_at(args, 1).unwrap();
+    /// assert_eq!(seen.find(GRAPH_ID).unwrap().count, 1);
+    /// let ready = match grp {
+    ///     CompletionGroup::Ready(ready) => ready,
+    ///     CompletionGroup::Unready{ .. } => unreachable!(),
+    /// };
+    /// let comps = ready.into_inner().0;
+    /// assert!(comps.contains(&Completion::new("--color", "color")));
+    /// ```
+    pub fn supplement_at(
+        &self,
+        args: impl Iterator<Item = String>,
+        cursor: usize,
+    ) -> Result<(Seen, CompletionGroup<ID>)> {
+        let mut seen = Seen::new();
+        let grp = self.supplement_with_seen_at(&mut seen, args, cursor)?;
+        Ok((seen, grp))
+    }
+
+    /// See [`Command::supplement_at`] and [`Command::supplement_with_seen`].
+    pub fn supplement_with_seen_at(
+        &self,
+        seen: &mut Seen,
+        args: impl Iterator<Item = String>,
+        cursor: usize,
+    ) -> Result<CompletionGroup<ID>> {
+        if cursor == 0 {
+            return Err(Error::ArgsTooShort);
+        }
+        self.supplement_with_seen_inner(seen, ArgIter::new(args, Some(cursor)))
+    }
+
+    fn supplement_with_seen_inner(
+        &self,
+        seen: &mut Seen,
+        mut args: ArgIter<impl Iterator<Item = String>>,
     ) -> Result<CompletionGroup<ID>> {
         args.next(); // ignore the first arg which is the program's name
 
-        let mut args = args.peekable();
-        if args.peek().is_none() {
+        if args.is_empty() {
             return Err(Error::ArgsTooShort);
         }
 
-        self.supplement_recur(&mut None, seen, &mut args)
+        let grp = self.supplement_recur(&mut None, seen, &mut args)?;
+        // NOTE: `None` means the cursor is beyond the args
+        grp.ok_or(Error::ArgsTooShort)
     }
 
     fn doing_external(&self, ctx: &ArgsContext<'_, ID>) -> bool {
         let has_subcmd = !self.commands.is_empty();
         has_subcmd && ctx.has_seen_arg()
     }
+    /// Everything is an arg for external subcommands, or after `--`.
+    fn disable_flag(&self, ctx: &ArgsContext<'_, ID>) -> bool {
+        self.doing_external(ctx) || ctx.is_options_ended()
+    }
     fn flags(&self, seen: &Seen) -> impl Iterator<Item = &Flag<ID>> {
         self.all_flags.iter().filter(|f| {
+            if f.conflicts_in_seen(seen) {
+                log::debug!("flag {:?} conflicts with what's seen", f.name());
+                return false;
+            }
             if !f.once {
                 true
             } else {
@@ -191,6 +397,33 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
         })
     }
 
+    /// The available flags, where the missing required ones are marked and prioritized.
+    /// `typed` is the long flag typed so far, without the leading `--`.
+    fn flag_completions(&self, seen: &Seen, is_long: Option<bool>, typed: &str) -> Vec<Completion> {
+        self.flags(seen)
+            .filter(|f| f.hidden.is_completed(f.long.iter(), typed))
+            .flat_map(|f| {
+                let missing = f.is_missing(seen);
+                f.gen_completion(is_long).map(move |mut comp| {
+                    if missing {
+                        comp.description = format!("(required) {}", comp.description);
+                        comp = comp.priority(1);
+                    }
+                    comp
+                })
+            })
+            .collect()
+    }
+
+    /// The flags of this command which are required but not seen yet.
+    pub fn missing_flags<'a>(&'a self, seen: &'a Seen) -> impl Iterator<Item = &'a Flag<ID>> {
+        self.flags(seen).filter(|f| f.is_missing(seen))
+    }
+    /// The args of this command which are required but not seen yet.
+    pub fn missing_args<'a>(&'a self, seen: &'a Seen) -> impl Iterator<Item = &'a Arg<ID>> {
+        self.args.iter().filter(|a| a.is_missing(seen))
+    }
+
     fn find_flag<F: FnMut(&Flag<ID>) -> bool>(
         &self,
         arg: &str,
@@ -204,19 +437,58 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
     }
 
     fn find_long_flag(&self, flag: &str, seen: &Seen) -> Result<&Flag<ID>> {
-        self.find_flag(flag, seen, |f| f.long.iter().any(|f| f == flag))
+        let exact = self.find_flag(flag, seen, |f| f.all_longs().any(|f| f == flag));
+        if exact.is_ok() || !self.infer_long_args {
+            return exact;
+        }
+        let mut candidates = self
+            .all_flags
+            .iter()
+            .filter(|f| f.all_longs().any(|l| l.starts_with(flag)));
+        match (candidates.next(), candidates.next()) {
+            (Some(inferred), None) => {
+                log::info!("infer flag {} from {}", inferred.name(), flag);
+                self.find_flag(flag, seen, |f| std::ptr::eq(f, inferred))
+            }
+            _ => exact,
+        }
+    }
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(&*self.name).chain(self.aliases.iter())
+    }
+    fn find_command(&self, name: &str) -> Option<&Command<ID>> {
+        let exact = self.commands.iter().find(|c| c.names().any(|n| n == name));
+        if exact.is_some() || !self.infer_subcommands {
+            return exact;
+        }
+        let mut candidates = self
+            .commands
+            .iter()
+            .filter(|c| c.names().any(|n| n.starts_with(name)));
+        match (candidates.next(), candidates.next()) {
+            (Some(inferred), None) => {
+                log::info!("infer subcommand {} from {}", inferred.name, name);
+                Some(inferred)
+            }
+            _ => None,
+        }
     }
     fn find_short_flag(&self, flag: char, seen: &Seen) -> Result<&Flag<ID>> {
-        self.find_flag(&flag.to_string(), seen, |f| f.short.contains(&flag))
+        self.find_flag(&flag.to_string(), seen, |f| {
+            f.short.contains(&flag) || f.hidden_short.contains(&flag)
+        })
     }
 
+    /// Returns `None` if the args run out before the cursor, which only happens after the cursor is passed.
     fn supplement_recur<'a>(
         &'a self,
         args_ctx_opt: &mut Option<ArgsContext<'a, ID>>,
         seen: &mut Seen,
-        args: &mut Peekable<impl Iterator<Item = String>>,
-    ) -> Result<CompletionGroup<ID>> {
-        let arg = args.next().unwrap();
+        args: &mut ArgIter<impl Iterator<Item = String>>,
+    ) -> Result<Option<CompletionGroup<ID>>> {
+        let Some(arg) = args.next() else {
+            return Ok(None);
+        };
 
         let args_ctx = if let Some(ctx) = args_ctx_opt {
             ctx
@@ -225,8 +497,10 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
             args_ctx_opt.as_mut().unwrap()
         };
 
-        if args.peek().is_none() {
-            return self.supplement_last(args_ctx, seen, arg);
+        if args.at_cursor() {
+            let grp = self.supplement_last(args_ctx, seen, arg)?;
+            self.supplement_after_cursor(args_ctx_opt, seen, args);
+            return Ok(Some(grp));
         }
 
         macro_rules! handle_flag {
@@ -239,21 +513,26 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
                 } else {
                     let res = $flag.supplement($seen, args)?;
                     if let Some(res) = res {
-                        return Ok(res);
+                        self.supplement_after_cursor(args_ctx_opt, $seen, args);
+                        return Ok(Some(res));
                     }
                 }
             };
         }
 
-        match parse_flag(&arg, self.doing_external(args_ctx)) {
-            ParsedFlag::SingleDash | ParsedFlag::DoubleDash | ParsedFlag::Empty => {
+        match parse_flag(&arg, self.disable_flag(args_ctx)) {
+            ParsedFlag::DoubleDash => {
+                log::info!("end of options");
+                args_ctx.end_options();
+            }
+            ParsedFlag::SingleDash | ParsedFlag::Empty => {
                 supplement_arg(seen, args_ctx, arg)?;
             }
             ParsedFlag::NotFlag => {
-                let command = if args_ctx.has_seen_arg() {
+                let command = if args_ctx.has_seen_arg() || args_ctx.is_options_ended() {
                     None
                 } else {
-                    self.commands.iter().find(|c| arg == c.name)
+                    self.find_command(&arg)
                 };
                 match command {
                     Some(command) => {
@@ -278,16 +557,37 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
         self.supplement_recur(args_ctx_opt, seen, args)
     }
 
+    /// Parse the rest of args into [`Seen`] after the completion is done.
+    /// These words may well be nonsense when user is editing in the middle, so errors are ignored.
+    fn supplement_after_cursor<'a>(
+        &'a self,
+        args_ctx_opt: &mut Option<ArgsContext<'a, ID>>,
+        seen: &mut Seen,
+        args: &mut ArgIter<impl Iterator<Item = String>>,
+    ) {
+        if args.is_empty() {
+            return;
+        }
+        log::debug!("parse the args after cursor");
+        match self.supplement_recur(args_ctx_opt, seen, args) {
+            Ok(None) => (),
+            Ok(Some(grp)) => log::warn!("ignore completion {grp:?} after cursor"),
+            Err(err) => log::info!("ignore error after cursor: {err:?}"),
+        }
+    }
+
     fn supplement_last(
         &self,
         args_ctx: &mut ArgsContext<'_, ID>,
         seen: &mut Seen,
         arg: String,
     ) -> Result<CompletionGroup<ID>> {
-        let ret: CompletionGroup<ID> = match parse_flag(&arg, self.doing_external(args_ctx)) {
+        let ret: CompletionGroup<ID> = match parse_flag(&arg, self.disable_flag(args_ctx)) {
             ParsedFlag::Empty | ParsedFlag::NotFlag => {
-                let cmd_slice = if args_ctx.has_seen_arg() {
-                    log::info!("no completion for subcmd because we've already seen some args");
+                let cmd_slice = if args_ctx.has_seen_arg() || args_ctx.is_options_ended() {
+                    log::info!(
+                        "no completion for subcmd because we've already seen some args or `--`"
+                    );
                     &[]
                 } else {
                     log::debug!("completion for {} subcommands", self.commands.len());
@@ -295,12 +595,29 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
                 };
                 let cmd_comps = cmd_slice
                     .iter()
+                    .filter(|c| c.hidden.is_completed(std::iter::once(&*c.name), &arg))
                     .map(|c| Completion::new(&c.name, &c.description).group("command"));
 
-                if let Some(arg_obj) = args_ctx.next_arg() {
+                let arg_obj = args_ctx.next_arg().filter(|a| {
+                    let conflicts = a.conflicts.iter().any(|id| seen.contains(*id));
+                    if conflicts {
+                        log::info!(
+                            "no completion for arg {:?} which conflicts with what's seen",
+                            a.id
+                        );
+                    }
+                    !conflicts
+                });
+                if let Some(arg_obj) = arg_obj {
                     log::debug!("completion for args {:?}", arg_obj.id);
                     let unready = Unready::new(String::new(), arg.clone()).preexist(cmd_comps);
-                    comp_with_possible(unready, &arg_obj.possible_values, arg, arg_obj.id)
+                    comp_with_possible(
+                        unready,
+                        &arg_obj.possible_values,
+                        arg,
+                        arg_obj.id,
+                        arg_obj.value_delimiter,
+                    )
                 } else {
                     if cmd_slice.is_empty() {
                         return Err(Error::UnexpectedArg(arg));
@@ -308,18 +625,14 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
                     CompletionGroup::new_ready(cmd_comps.collect(), arg)
                 }
             }
-            ParsedFlag::DoubleDash | ParsedFlag::Long { equal: None, .. } => check_no_flag(
-                arg,
-                self.flags(seen)
-                    .flat_map(|f| f.gen_completion(Some(true)))
-                    .collect(),
-            )?,
-            ParsedFlag::SingleDash => check_no_flag(
-                arg,
-                self.flags(seen)
-                    .flat_map(|f| f.gen_completion(None))
-                    .collect(),
-            )?,
+            ParsedFlag::DoubleDash => {
+                check_no_flag(arg, self.flag_completions(seen, Some(true), ""))?
+            }
+            ParsedFlag::Long { equal: None, body } => {
+                let comps = self.flag_completions(seen, Some(true), body);
+                check_no_flag(arg, comps)?
+            }
+            ParsedFlag::SingleDash => check_no_flag(arg, self.flag_completions(seen, None, ""))?,
             ParsedFlag::Long {
                 equal: Some(value),
                 body,
@@ -332,7 +645,13 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
                 let prefix = format!("--{body}=");
                 let value = value.to_string();
                 let unready = Unready::new(prefix, arg);
-                comp_with_possible(unready, &valued.possible_values, value, valued.id)
+                comp_with_possible(
+                    unready,
+                    &valued.possible_values,
+                    value,
+                    valued.id,
+                    valued.value_delimiter,
+                )
             }
             ParsedFlag::Shorts => self.supplement_last_short_flags(seen, arg)?,
         };
@@ -428,15 +747,22 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
                     }
                 }
                 let prefix = format!("{}{}", resolved.flag_part, eq);
-                let unready = Unready::new(prefix, arg);
-                comp_with_possible(unready, &valued.possible_values, value, valued.id)
+                // Match against the value only, so a bare `-af` is an empty value after `-af=`
+                let unready = Unready::new(prefix.clone(), format!("{prefix}{value}"));
+                comp_with_possible(
+                    unready,
+                    &valued.possible_values,
+                    value,
+                    valued.id,
+                    valued.value_delimiter,
+                )
             }
             flag_type::Type::Bool(inner) => {
                 log::debug!("list short flags with seen {:?}", seen);
                 inner.push(seen);
                 let comps = self
-                    .flags(seen)
-                    .flat_map(|f| f.gen_completion(Some(false)))
+                    .flag_completions(seen, Some(false), "")
+                    .into_iter()
                     .map(|c| {
                         c.value(|v| {
                             let flag = &v[1..]; // skip the first '-' character
@@ -451,6 +777,42 @@ impl<ID: 'static + Copy + PartialEq + Debug> Command<ID> {
     }
 }
 
+/// The args to parse, which knows if the arg just taken is under the cursor.
+pub(crate) struct ArgIter<I: Iterator<Item = String>> {
+    inner: Peekable<I>,
+    /// Index of the next arg.
+    idx: usize,
+    /// Index of the arg under cursor. `None` means the last one.
+    cursor: Option<usize>,
+}
+impl<I: Iterator<Item = String>> ArgIter<I> {
+    fn new(inner: I, cursor: Option<usize>) -> Self {
+        ArgIter {
+            inner: inner.peekable(),
+            idx: 0,
+            cursor,
+        }
+    }
+    pub(crate) fn next(&mut self) -> Option<String> {
+        let arg = self.inner.next()?;
+        self.idx += 1;
+        Some(arg)
+    }
+    pub(crate) fn is_empty(&mut self) -> bool {
+        self.inner.peek().is_none()
+    }
+    pub(crate) fn peek(&mut self) -> Option<&str> {
+        self.inner.peek().map(|s| s.as_str())
+    }
+    /// If the arg just taken by [`ArgIter::next`] is under the cursor.
+    pub(crate) fn at_cursor(&mut self) -> bool {
+        match self.cursor {
+            Some(cursor) => self.idx == cursor + 1,
+            None => self.is_empty(),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 struct ResolvedMultiShort<'a, 'b, ID> {
     flag_part: &'a str,
@@ -1,4 +1,5 @@
 use super::Trace;
+use crate::core::Hidden;
 use crate::error::GenerateError;
 use std::collections::HashMap;
 
@@ -42,6 +43,7 @@ pub struct Config {
     ignore: HashMap<Vec<String>, MayBeProcessed>,
     custom: HashMap<Vec<String>, MayBeProcessed>,
     strict: bool,
+    hidden: Hidden,
 }
 
 impl Default for Config {
@@ -53,6 +55,7 @@ impl Config {
     pub fn new() -> Self {
         Config {
             strict: true,
+            hidden: Hidden::Prefixed,
             ignore: Default::default(),
             custom: Default::default(),
         }
@@ -120,6 +123,7 @@ impl Config {
     pub(crate) fn check_unprocessed_config(self) -> Result<(), GenerateError> {
         let Config {
             strict: _,
+            hidden: _,
             ignore,
             custom,
         } = self;
@@ -141,4 +145,18 @@ impl Config {
     pub fn is_strict(&self) -> bool {
         self.strict
     }
+    /// How the flags and subcommands hidden in clap (i.e. `hide(true)`) are completed.
+    /// They are always parsed anyway. Defaults to [`Hidden::Prefixed`].
+    /// ```no_run
+    /// # use supplement::generate::Config;
+    /// # use supplement::core::Hidden;
+    /// let config = Config::default().hidden(Hidden::Always); // never complete the hidden ones
+    /// ```
+    pub fn hidden(mut self, hidden: Hidden) -> Self {
+        self.hidden = hidden;
+        self
+    }
+    pub(crate) fn get_hidden(&self, is_hide_set: bool) -> Hidden {
+        if is_hide_set { self.hidden } else { Hidden::No }
+    }
 }
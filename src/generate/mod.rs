@@ -3,7 +3,9 @@ use std::io::Write;
 
 mod config;
 mod utils;
-use crate::abstraction::{Arg, ArgAction, ClapCommand, Command, PossibleValue};
+use crate::abstraction::{
+    Arg, ArgAction, ClapCommand, Command, PossibleValue, Relations, Requirement,
+};
 pub use config::Config;
 use utils::{Join, ctx_func, gen_enum_name, gen_rust_name, to_screaming_snake_case, to_snake_case};
 
@@ -175,18 +177,80 @@ impl FlagDisplayHelper<'_> {
                     .map_err(|msg| GenerateError::Strict { id, msg })?;
                 let possible_values = self.flag.get_possible_values();
                 let possible_values = format_possible_values(&possible_values);
-                format!(
-                    "flag_type::Type::new_valued({id_value}, {id_name}.into(), {complete_with_equal}, {possible_values})"
-                )
+                let (min, max) = (self.flag.get_min_num_args(), self.flag.get_max_num_args());
+                let delimiter = self.flag.get_value_delimiter();
+                if max > 1 || delimiter.is_some() {
+                    format!(
+                        "flag_type::Type::new_multi_valued({id_value}, {id_name}.into(), {complete_with_equal}, {possible_values}, {min}, {max}, {delimiter:?})"
+                    )
+                } else {
+                    format!(
+                        "flag_type::Type::new_valued({id_value}, {id_name}.into(), {complete_with_equal}, {possible_values})"
+                    )
+                }
             }
         };
         Ok(s)
     }
 }
 
+/// The ID name of another arg in the command, or `None` if it's not generated.
+fn gen_other_id(other: &Arg<'_>, config: &mut Config, prev: &[Trace]) -> Option<String> {
+    let name = other.get_id().to_string();
+    if config.is_ignored(prev, &name) {
+        return None;
+    }
+    let rust_name = gen_rust_name(NameType::VAL, &name);
+    Some(to_screaming_snake_case(&format!("id_{rust_name}")))
+}
+
+/// The seen IDs of the args in conflict with `arg`, skipping those not generated.
+fn gen_conflicts(
+    arg: &Arg<'_>,
+    relations: &Relations<'_>,
+    config: &mut Config,
+    prev: &[Trace],
+) -> String {
+    let conflicts = relations.conflicts.get(arg.get_id()).into_iter().flatten();
+    let conflicts = conflicts.filter_map(|other| {
+        let id_name = gen_other_id(other, config, prev)?;
+        Some(format!("{id_name}.any()"))
+    });
+    let conflicts: Vec<_> = conflicts.collect();
+    format!("CowSlice::Borrow(&[{}])", Join(conflicts.iter()))
+}
+
+/// When `arg` is required, skipping the args not generated.
+fn gen_required(
+    arg: &Arg<'_>,
+    relations: &Relations<'_>,
+    config: &mut Config,
+    prev: &[Trace],
+) -> String {
+    let required = match relations.requirements.get(arg.get_id()) {
+        None => None,
+        Some(Requirement::Always) => Some("Required::Always".to_string()),
+        Some(Requirement::UnlessPresent(others)) => {
+            let ids = others.iter().filter_map(|other| {
+                let id_name = gen_other_id(other, config, prev)?;
+                Some(format!("{id_name}.any()"))
+            });
+            let ids: Vec<_> = ids.collect();
+            (!ids.is_empty()).then(|| {
+                format!(
+                    "Required::UnlessPresent(CowSlice::Borrow(&[{}]))",
+                    Join(ids.iter())
+                )
+            })
+        }
+    };
+    format!("CowSlice::Borrow(&[{}])", Join(required.iter()))
+}
+
 fn generate_args_in_cmd(
     indent: &str,
     cmd: &Command<'_>,
+    relations: &Relations<'_>,
     config: &mut Config,
     prev: &[Trace],
     w: &mut impl Write,
@@ -200,33 +264,56 @@ fn generate_args_in_cmd(
             "@ext".to_string(),
             name,
             usize::MAX,
+            false,
+            None,
             vec![],
+            "CowSlice::Borrow(&[])".to_string(),
+            "CowSlice::Borrow(&[])".to_string(),
             NameType::EXTERNAL,
         ))
     } else {
         None
     };
-    let args = utils::args(cmd).map(|arg| {
-        let name = arg.get_id().to_string();
-
-        log::debug!("generating arg {}", name);
-
-        let max_values = arg.get_max_num_args();
-        let rust_name = gen_rust_name(NameType::VAL, &name);
-
-        (
-            name,
-            rust_name,
-            max_values,
-            arg.get_possible_values(),
-            NameType::VAL,
-        )
-    });
-    let args = args.chain(ext_sub);
-
-    for (name, rust_name, max_values, possible_values, name_type) in args {
+    let args: Vec<_> = utils::args(cmd)
+        .map(|arg| {
+            let name = arg.get_id().to_string();
+
+            log::debug!("generating arg {}", name);
+
+            let max_values = arg.get_max_num_args();
+            let rust_name = gen_rust_name(NameType::VAL, &name);
+            let conflicts = gen_conflicts(&arg, relations, config, prev);
+            let required = gen_required(&arg, relations, config, prev);
+
+            (
+                name,
+                rust_name,
+                max_values,
+                arg.is_last_set(),
+                arg.get_value_delimiter(),
+                arg.get_possible_values(),
+                conflicts,
+                required,
+                NameType::VAL,
+            )
+        })
+        .collect();
+    let args = args.into_iter().chain(ext_sub);
+
+    for (
+        name,
+        rust_name,
+        max_values,
+        last,
+        value_delimiter,
+        possible_values,
+        conflicts,
+        required,
+        name_type,
+    ) in args
+    {
         let id_name = to_screaming_snake_case(&format!("id_{rust_name}"));
-        let (id_type, ty) = if max_values == 1 {
+        let (id_type, ty) = if max_values == 1 && value_delimiter.is_none() {
             ("id::SingleVal", ValType::Single)
         } else {
             ("id::MultiVal", ValType::Multi)
@@ -250,7 +337,11 @@ fn generate_args_in_cmd(
 {indent}    id: {id_value},
 {indent}    seen_id: {id_name}.into(),
 {indent}    max_values: {max_values},
+{indent}    last: {last},
+{indent}    value_delimiter: {value_delimiter:?},
 {indent}    possible_values: {possible_values},
+{indent}    conflicts: {conflicts},
+{indent}    required: {required},
 {indent}}};"
         )?;
 
@@ -271,6 +362,7 @@ fn generate_flags_in_cmd(
     indent: &str,
     config: &mut Config,
     cmd: &Command<'_>,
+    relations: &Relations<'_>,
     global_flags: &mut Vec<GlobalFlag>,
     w: &mut impl Write,
 ) -> Result<Vec<ValUnit>, GenerateError> {
@@ -314,13 +406,18 @@ fn generate_flags_in_cmd(
 
         let shorts = flag.get_short_and_visible_aliases().unwrap_or_default();
         let longs = flag.get_long_and_visible_aliases().unwrap_or_default();
+        let hidden_shorts = flag.get_hidden_short_aliases();
+        let hidden_longs = flag.get_hidden_aliases();
+        let hidden = config.get_hidden(flag.is_hide_set());
 
         let (once, ty) = match flag.get_action() {
             ArgAction::Count => (false, ValType::No),
             ArgAction::Append => (false, ValType::Multi),
             _ => {
-                let once = !flag.is_global_set();
-                if takes_values {
+                let once = !flag.is_global_set() && !cmd.is_args_override_self();
+                if flag.get_max_num_args() > 1 || flag.get_value_delimiter().is_some() {
+                    (once, ValType::Multi)
+                } else if takes_values {
                     (once, ValType::Single)
                 } else {
                     // TODO: for `once`, should also check override self
@@ -329,9 +426,22 @@ fn generate_flags_in_cmd(
             }
         };
         let description = utils::escape_help(&flag.get_help());
+        let negation = relations.negations.get(flag.get_id());
+        let once = once && !negation.as_ref().is_some_and(|n| n.overridable);
+        let conflicts = gen_conflicts(&flag, relations, config, prev);
+        let required = gen_required(&flag, relations, config, prev);
+        // The other flag of the negation pair, if that one is generated.
+        let opposite = negation.and_then(|n| {
+            let is_negative = n.negative.get_id() == flag.get_id();
+            let other = if is_negative { n.positive } else { n.negative };
+            let id_name = gen_other_id(&other, config, prev)?;
+            Some((is_negative, id_name))
+        });
 
         let shorts = Join(shorts.iter().map(|s| format!("'{s}'")));
         let longs = Join(longs.iter().map(|s| format!("\"{s}\"")));
+        let hidden_shorts = Join(hidden_shorts.iter().map(|s| format!("'{s}'")));
+        let hidden_longs = Join(hidden_longs.iter().map(|s| format!("\"{s}\"")));
         let id_name = to_screaming_snake_case(&format!("id_{rust_name}"));
         let flag_display_helper = FlagDisplayHelper {
             ty,
@@ -346,18 +456,30 @@ fn generate_flags_in_cmd(
         let enum_name = gen_enum_name(NameType::VAL, &name);
         let enum_name = if is_static { None } else { Some(enum_name) };
 
-        let id_line = flag_display_helper.id_line_str();
-        let type_str = flag_display_helper.type_str()?;
+        let type_str = match opposite {
+            Some((true, positive_id)) => {
+                format!("flag_type::Type::new_negation({id_name}, {positive_id})")
+            }
+            Some((false, negative_id)) => {
+                format!("flag_type::Type::new_negatable({id_name}, {negative_id})")
+            }
+            None => flag_display_helper.type_str()?,
+        };
 
+        writeln!(w, "{indent}{}", flag_display_helper.id_line_str())?;
         writeln!(
             w,
             "\
-{indent}{id_line}
 {indent}const {rust_name}: Flag<GlobalID> = Flag {{
 {indent}    short: CowSlice::Borrow(&[{shorts}]),
 {indent}    long: CowOwned::Borrow(&[{longs}]),
+{indent}    hidden_short: CowSlice::Borrow(&[{hidden_shorts}]),
+{indent}    hidden_long: CowOwned::Borrow(&[{hidden_longs}]),
+{indent}    hidden: Hidden::{hidden:?},
 {indent}    description: Cow::Borrowed(\"{description}\"),
 {indent}    once: {once},
+{indent}    conflicts: {conflicts},
+{indent}    required: {required},
 {indent}    ty: {type_str},
 {indent}}};"
         )?;
@@ -407,8 +529,10 @@ fn generate_recur(
         }
         writeln!(w, "{indent}use supplement::gen_prelude::*;\n")?;
 
-        let flags = generate_flags_in_cmd(prev, indent, config, cmd, &mut global_flags, w)?;
-        let args = generate_args_in_cmd(indent, cmd, config, prev, w)?;
+        let relations = cmd.get_relations();
+        let flags =
+            generate_flags_in_cmd(prev, indent, config, cmd, &relations, &mut global_flags, w)?;
+        let args = generate_args_in_cmd(indent, cmd, &relations, config, prev, w)?;
 
         let mut sub_cmds: Vec<CmdUnit> = vec![];
         for sub_cmd in utils::non_help_subcmd(cmd) {
@@ -488,6 +612,14 @@ fn generate_recur(
                 .map(|x| format!("{}::{}", x.mod_name, cmd_name)),
         );
         let scope = if level == 0 { "" } else { "(super)" };
+        let aliases = Join(
+            cmd.get_all_aliases()
+                .into_iter()
+                .map(|s| format!("\"{s}\"")),
+        );
+        let hidden = config.get_hidden(cmd.is_hide_set());
+        let infer_long_args = cmd.is_infer_long_args_set();
+        let infer_subcommands = cmd.is_infer_subcommands_set();
 
         writeln!(
             w,
@@ -495,9 +627,13 @@ fn generate_recur(
 {indent}pub{scope} const {cmd_name}: Command<GlobalID> = Command {{
 {indent}    name: Cow::Borrowed(\"{name}\"),
 {indent}    description: Cow::Borrowed(\"{description}\"),
+{indent}    aliases: CowOwned::Borrow(&[{aliases}]),
+{indent}    hidden: Hidden::{hidden:?},
 {indent}    all_flags: CowSlice::Borrow(&[{flags}]),
 {indent}    args: CowSlice::Borrow(&[{args}]),
 {indent}    commands: CowSlice::Borrow(&[{sub_cmds}]),
+{indent}    infer_long_args: {infer_long_args},
+{indent}    infer_subcommands: {infer_subcommands},
 {indent}}};"
         )?;
     }
@@ -49,10 +49,22 @@ pub enum Valued {
     Multi(MultiVal),
 }
 
+/// Id of any kind, to refer to other CLI objects, e.g. the flags in conflict.
+#[doc(hidden)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Any {
+    No(NoVal),
+    Single(SingleVal),
+    Multi(MultiVal),
+}
+
 impl NoVal {
     pub const fn new(id: u32) -> Self {
         NoVal(id)
     }
+    pub const fn any(self) -> Any {
+        Any::No(self)
+    }
 }
 impl SingleVal {
     pub const fn new(id: u32) -> Self {
@@ -61,6 +73,9 @@ impl SingleVal {
     pub const fn into(self) -> Valued {
         Valued::Single(self)
     }
+    pub const fn any(self) -> Any {
+        Any::Single(self)
+    }
 }
 impl MultiVal {
     pub const fn new(id: u32) -> Self {
@@ -69,4 +84,15 @@ impl MultiVal {
     pub const fn into(self) -> Valued {
         Valued::Multi(self)
     }
+    pub const fn any(self) -> Any {
+        Any::Multi(self)
+    }
+}
+impl Valued {
+    pub const fn any(self) -> Any {
+        match self {
+            Valued::Single(id) => Any::Single(id),
+            Valued::Multi(id) => Any::Multi(id),
+        }
+    }
 }
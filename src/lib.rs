@@ -1,18 +1,23 @@
 //! Qucikstart: Check the example of [`Supplement`].
 //! Derive it and call [`Supplement::supplement`] to start the CLI compleiton.
 
+mod complete_env;
 pub mod completion;
 pub mod core;
 pub mod error;
 pub mod gen_prelude;
 pub mod id;
+pub mod matcher;
+pub mod script;
 pub mod seen;
 mod supplement;
+pub mod tokenize;
 
 pub(crate) mod abstraction;
 pub(crate) mod arg_context;
 pub(crate) mod parsed_flag;
 
+pub use complete_env::CompleteEnv;
 pub use completion::{Completion, CompletionGroup};
 pub use seen::Seen;
 pub use supplement::Supplement;
@@ -49,6 +54,28 @@ pub enum Shell {
     Zsh,
     Fish,
     Bash,
+    /// PowerShell (Core). Parsed from either `powershell` or `pwsh`.
+    PowerShell,
+    /// Nushell's external completer. Parsed from either `nushell` or `nu`.
+    Nushell,
+    /// Elvish. Parsed from `elvish` or `elv`.
+    Elvish,
+    /// Xonsh. Parsed from `xonsh`.
+    Xonsh,
+}
+impl Shell {
+    /// The name of the shell, which can be parsed back with [`str::parse`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Bash => "bash",
+            Shell::PowerShell => "powershell",
+            Shell::Nushell => "nushell",
+            Shell::Elvish => "elvish",
+            Shell::Xonsh => "xonsh",
+        }
+    }
 }
 impl std::str::FromStr for Shell {
     type Err = String;
@@ -57,6 +84,10 @@ impl std::str::FromStr for Shell {
             "zsh" => Shell::Zsh,
             "fish" => Shell::Fish,
             "bash" => Shell::Bash,
+            "powershell" | "pwsh" => Shell::PowerShell,
+            "nushell" | "nu" => Shell::Nushell,
+            "elvish" | "elv" => Shell::Elvish,
+            "xonsh" => Shell::Xonsh,
             _ => return Err(format!("Unknown shell {}", s)),
         };
 
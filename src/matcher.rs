@@ -0,0 +1,262 @@
+//! The module for matching strategies, which decide what candidates [`crate::completion::Ready::print`]
+//! shows for the value on command-line, and in what order.
+//!
+//! ```
+//! use supplement::matcher::{Matcher, Prefix, Fuzzy};
+//!
+//! assert!(Prefix.matches("checkout", "che").is_some());
+//! assert!(Prefix.matches("checkout", "cko").is_none());
+//!
+//! // Contiguous and prefix matches are better
+//! let good = Fuzzy.matches("checkout", "che").unwrap();
+//! let bad = Fuzzy.matches("cherry-pick", "chk").unwrap();
+//! assert!(good.score > bad.score);
+//! // The positions (in characters) of the matched characters, for highlighting
+//! assert_eq!(bad.positions, vec![0, 1, 10]);
+//! ```
+
+use std::fmt::Debug;
+
+/// The result of a successful match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Match {
+    /// Higher is better. Candidates with the same score keep their original order.
+    pub score: i64,
+    /// The character indices of the candidate that matched, in ascending order. Might be empty if unknown.
+    pub positions: Vec<usize>,
+}
+impl Match {
+    pub fn new(score: i64) -> Self {
+        Match {
+            score,
+            positions: vec![],
+        }
+    }
+    pub fn positions(mut self, positions: Vec<usize>) -> Self {
+        self.positions = positions;
+        self
+    }
+}
+
+/// A matching strategy. Implement this trait for custom strategies.
+pub trait Matcher: Debug {
+    /// Returns `None` if `value` (the candidate) doesn't match `pattern` (the value on command-line).
+    fn matches(&self, value: &str, pattern: &str) -> Option<Match>;
+}
+
+/// `value` starts with `pattern`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Prefix;
+impl Matcher for Prefix {
+    fn matches(&self, value: &str, pattern: &str) -> Option<Match> {
+        value
+            .starts_with(pattern)
+            .then(|| Match::new(0).positions(prefix_positions(pattern)))
+    }
+}
+
+/// `value` starts with `pattern`, ignoring case. Those with the exact case are ranked higher.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IgnoreCase;
+impl Matcher for IgnoreCase {
+    fn matches(&self, value: &str, pattern: &str) -> Option<Match> {
+        if value.starts_with(pattern) {
+            return Some(Match::new(1).positions(prefix_positions(pattern)));
+        }
+        starts_with_ignore_case(value, pattern)
+            .then(|| Match::new(0).positions(prefix_positions(pattern)))
+    }
+}
+
+/// Like [`IgnoreCase`] if `pattern` is all lowercase, otherwise like [`Prefix`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SmartCase;
+impl Matcher for SmartCase {
+    fn matches(&self, value: &str, pattern: &str) -> Option<Match> {
+        if pattern.chars().any(char::is_uppercase) {
+            Prefix.matches(value, pattern)
+        } else {
+            IgnoreCase.matches(value, pattern)
+        }
+    }
+}
+
+/// `value` contains `pattern`. The earlier it appears, the better.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Substring;
+impl Matcher for Substring {
+    fn matches(&self, value: &str, pattern: &str) -> Option<Match> {
+        let pos = value.find(pattern)?;
+        let pos = value[..pos].chars().count();
+        let positions = (pos..pos + pattern.chars().count()).collect();
+        Some(Match::new(-(pos as i64)).positions(positions))
+    }
+}
+
+/// `value` contains all characters of `pattern` in order, e.g. `chk` matches `checkout`.
+/// This is the default strategy.
+///
+/// The score rewards contiguous runs, matches at word boundaries (after `-`, `_`, `/`, etc. or camelCase humps),
+/// and matches at the very beginning, while gaps between matched characters are penalised.
+/// Among all possible alignments, the best one is taken.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fuzzy;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_PREFIX: i64 = 16;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 8;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+impl Fuzzy {
+    fn bonus(value: &[char], i: usize) -> i64 {
+        let Some(prev) = i.checked_sub(1).map(|i| value[i]) else {
+            return BONUS_PREFIX;
+        };
+        let cur = value[i];
+        let is_sep = |c: char| !c.is_alphanumeric();
+        if (is_sep(prev) && !is_sep(cur)) || (prev.is_lowercase() && cur.is_uppercase()) {
+            BONUS_BOUNDARY
+        } else {
+            0
+        }
+    }
+}
+
+impl Matcher for Fuzzy {
+    fn matches(&self, value: &str, pattern: &str) -> Option<Match> {
+        let value: Vec<char> = value.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+        if pattern.is_empty() {
+            return Some(Match::new(0));
+        }
+
+        let mut rest = value.iter();
+        if !pattern.iter().all(|p| rest.any(|v| v == p)) {
+            return None;
+        }
+
+        // `prev[i]` and `cur[i]`: the best score when `pattern[j - 1]` and `pattern[j]` match `value[i]`
+        let n = value.len();
+        let mut prev: Vec<Option<i64>> = vec![None; n];
+        let mut cur: Vec<Option<i64>> = vec![None; n];
+        // `from[(j - 1) * n + i]`: where `pattern[j - 1]` matches for the best score of `cur[i]`
+        let mut from = vec![0; (pattern.len() - 1) * n];
+        for (j, &p) in pattern.iter().enumerate() {
+            // The best score through a gap, i.e. `prev[k]` with the gap penalty for some `k < i - 1`
+            let mut gap: Option<(i64, usize)> = None;
+            for i in 0..n {
+                gap = gap.map(|(score, k)| (score - PENALTY_GAP_EXTENSION, k));
+                if let Some(k) = i.checked_sub(2)
+                    && let Some(score) = prev[k].map(|s| s - PENALTY_GAP_START)
+                    && gap.is_none_or(|(s, _)| score > s)
+                {
+                    gap = Some((score, k));
+                }
+
+                cur[i] = None;
+                if value[i] != p {
+                    continue;
+                }
+                let base = SCORE_MATCH + Self::bonus(&value, i);
+                if j == 0 {
+                    cur[i] = Some(base);
+                    continue;
+                }
+                let consecutive = i
+                    .checked_sub(1)
+                    .and_then(|k| Some((prev[k]? + BONUS_CONSECUTIVE, k)));
+                // Ties go to the earlier match
+                let best = match (gap, consecutive) {
+                    (Some(g), Some(c)) if c.0 > g.0 => Some(c),
+                    (g, c) => g.or(c),
+                };
+                if let Some((score, k)) = best {
+                    cur[i] = Some(score + base);
+                    from[(j - 1) * n + i] = k;
+                }
+            }
+            std::mem::swap(&mut prev, &mut cur);
+        }
+
+        let (mut i, score) = prev
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.map(|score| (i, score)))
+            .fold(None, |acc: Option<(usize, i64)>, (i, score)| match acc {
+                Some((_, s)) if s >= score => acc,
+                _ => Some((i, score)),
+            })?;
+
+        let mut positions = vec![i; pattern.len()];
+        for j in (1..pattern.len()).rev() {
+            i = from[(j - 1) * n + i];
+            positions[j - 1] = i;
+        }
+        Some(Match::new(score).positions(positions))
+    }
+}
+
+/// `0..len` of the pattern, in characters
+fn prefix_positions(pattern: &str) -> Vec<usize> {
+    (0..pattern.chars().count()).collect()
+}
+
+fn starts_with_ignore_case(value: &str, pattern: &str) -> bool {
+    let mut value = value.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|ch| value.next() == Some(ch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn score(m: &dyn Matcher, value: &str, pattern: &str) -> Option<i64> {
+        m.matches(value, pattern).map(|m| m.score)
+    }
+
+    #[test]
+    fn test_matchers() {
+        assert_eq!(score(&Prefix, "Main", "ma"), None);
+        assert_eq!(score(&IgnoreCase, "Main", "ma"), Some(0));
+        assert_eq!(score(&IgnoreCase, "main", "ma"), Some(1));
+        assert_eq!(score(&SmartCase, "Main", "ma"), Some(0));
+        assert_eq!(score(&SmartCase, "main", "Ma"), None);
+        assert_eq!(score(&Substring, "domain", "ma"), Some(-2));
+        assert_eq!(
+            Substring.matches("ĉemain", "ma").unwrap().positions,
+            vec![2, 3]
+        );
+        assert_eq!(score(&Fuzzy, "checkout", "ox"), None);
+        assert_eq!(score(&Fuzzy, "checkout", ""), Some(0));
+    }
+
+    #[test]
+    fn test_fuzzy() {
+        let positions =
+            |value: &str, pattern: &str| Fuzzy.matches(value, pattern).unwrap().positions;
+        // Prefer contiguous runs and word boundaries over the first occurrence
+        assert_eq!(positions("ma-main", "main"), vec![3, 4, 5, 6]);
+        assert_eq!(positions("cherry-pick", "cp"), vec![0, 7]);
+        assert_eq!(positions("showCommitPatch", "CP"), vec![4, 10]);
+        assert_eq!(positions("ĉeĉo", "ĉo"), vec![0, 3]);
+
+        fn rank<'a>(pattern: &str, values: &[&'a str]) -> Vec<&'a str> {
+            let mut ranked: Vec<_> = values
+                .iter()
+                .filter_map(|v| Some((score(&Fuzzy, v, pattern)?, *v)))
+                .collect();
+            ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            ranked.into_iter().map(|(_, v)| v).collect()
+        }
+        assert_eq!(
+            rank("ma", &["remote-add", "feature/main", "master", "tmp/ma"]),
+            vec!["master", "feature/main", "tmp/ma", "remote-add"]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+_supplement_{fn}()
+{
+    local -a words
+    local cword cur
+    if declare -F _get_comp_words_by_ref >/dev/null; then
+        # Don't split on `=` and `:`, the completer takes care of them
+        _get_comp_words_by_ref -n =: -w words -i cword -c cur
+    else
+        words=("${COMP_WORDS[@]}")
+        cword=$COMP_CWORD
+        cur="${COMP_WORDS[COMP_CWORD]}"
+    fi
+
+    local out
+    # Pass the words up to the cursor as separate arguments.
+    # The word under cursor is an empty string if there's a trailing white space.
+    if ! out=$({invoke} "${words[@]:0:$((cword+1))}"); then
+        # fall back to default completion
+        mapfile -t COMPREPLY < <(compgen -f -- "$cur")
+        return
+    fi
+
+    COMPREPLY=()
+    local line kind prefix arg glob path
+    local -a paths
+    while IFS= read -r line; do
+        if [[ $line != $'\t'* ]]; then
+            COMPREPLY+=("$line")
+            continue
+        fi
+
+        # A line without value is a directive: `\t<kind>\t<prefix>\t<arg>\t<glob>`
+        line=${line#$'\t'}
+        kind=${line%%$'\t'*}
+        line=${line#*$'\t'}
+        prefix=${line%%$'\t'*}
+        line=${line#*$'\t'}
+        arg=${line%%$'\t'*}
+        glob=${line#*$'\t'}
+        case $kind in
+            files) mapfile -t paths < <(compgen -f -- "$arg") ;;
+            dirs) mapfile -t paths < <(compgen -d -- "$arg") ;;
+            glob) mapfile -t paths < <(compgen -d -- "$arg"; compgen -f -X "!$glob" -- "$arg") ;;
+            nospace) compopt -o nospace 2>/dev/null; continue ;;
+            nosort) compopt -o nosort 2>/dev/null; continue ;;
+            *) continue ;;
+        esac
+        # Let bash quote the values and append slashes to directories
+        compopt -o filenames 2>/dev/null
+        for path in "${paths[@]}"; do
+            COMPREPLY+=("$prefix$path")
+        done
+    done <<< "$out"
+
+    # Bash only replaces the part after the last `=` or `:`
+    local cur_prefix="${cur%"${cur##*[=:]}"}"
+    if [[ -n "$cur_prefix" ]]; then
+        COMPREPLY=("${COMPREPLY[@]#"$cur_prefix"}")
+    fi
+} &&
+    complete -F _supplement_{fn} {bin}
+
+# ex: filetype=sh
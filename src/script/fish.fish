@@ -0,0 +1,39 @@
+function __supplement_{fn}
+    # The tokens before the cursor, and the one under cursor (empty if there's a trailing white space)
+    set -l words (commandline -opc) (commandline -ct)
+    set -l out ({invoke} $words)
+    or begin
+        __fish_complete_path (commandline -ct) # fall back to default completion
+        return
+    end
+
+    for line in $out
+        set -l fields (string split \t -- $line)
+        if test -n "$fields[1]"
+            printf '%s\n' $line
+            continue
+        end
+
+        # A line without value is a directive: `\t<kind>\t<prefix>\t<arg>\t<glob>`
+        set -l paths
+        switch $fields[2]
+            case files
+                set paths (__fish_complete_path $fields[4])
+            case dirs
+                set paths (__fish_complete_directories $fields[4])
+            case glob
+                for path in (__fish_complete_path $fields[4])
+                    set -l name (string split \t -- $path)[1]
+                    if string match -q -- '*/' $name
+                        or string match -q -- $fields[5] (string replace -r -- '.*/' '' $name)
+                        set -a paths $path
+                    end
+                end
+        end
+        for path in $paths
+            printf '%s\n' $fields[3]$path
+        end
+    end
+end
+
+complete -k -c {bin} -x -a "(__supplement_{fn})"
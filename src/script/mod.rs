@@ -0,0 +1,228 @@
+//! The module for shell registration scripts.
+//!
+//! A registration script tells the shell how to call your completer binary.
+//! Every script calls the binary as `<completer_path> <shell> <words...>`,
+//! where `words` are the words on command-line up to the cursor (the program name included).
+//! The word under cursor is always passed, even if it's empty, i.e. `qit log <TAB>` results in
+//! `<completer_path> fish qit log ''`.
+//!
+//! So the binary should look at the first argument to decide which shell to print for,
+//! and feed the rest to [`crate::Supplement::supplement`].
+//!
+//! Bash passes all the words as typed, and fish passes the word under cursor as typed,
+//! so they should go through [`crate::tokenize::unquote`] first,
+//! and the quoting state should be set with [`crate::completion::Ready::quote`].
+//!
+//! ```no_run
+//! use supplement::{Shell, script};
+//!
+//! // e.g. `my-app completions fish`
+//! let shell: Shell = std::env::args().nth(2).unwrap().parse().unwrap();
+//! let completer = std::env::current_exe().unwrap();
+//! let completer = completer.to_str().unwrap();
+//! script::render(shell, "my-app", completer, &mut std::io::stdout()).unwrap();
+//! ```
+
+use crate::Shell;
+use std::io::Result as IoResult;
+use std::io::Write;
+
+const BASH: &str = include_str!("bash.sh");
+const ZSH: &str = include_str!("zsh.zsh");
+const FISH: &str = include_str!("fish.fish");
+const POWERSHELL: &str = include_str!("powershell.ps1");
+const NUSHELL: &str = include_str!("nushell.nu");
+const ELVISH: &str = include_str!("elvish.elv");
+const XONSH: &str = include_str!("xonsh.xsh");
+
+/// How the script calls the completer binary.
+pub(crate) enum Invoke<'a> {
+    /// `<completer_path> <shell> <words...>`
+    Positional(&'a str),
+    /// `<var>=<shell> <completer_path> -- <words...>`, see [`crate::CompleteEnv`].
+    Env {
+        var: &'a str,
+        completer_path: &'a str,
+    },
+}
+
+impl Invoke<'_> {
+    /// The command prefix, after which the script appends the words on command-line.
+    fn to_prefix(&self, shell: Shell) -> String {
+        let name = shell.name();
+        match *self {
+            Invoke::Positional(path) => match shell {
+                Shell::Bash | Shell::Zsh => format!("{} {name}", posix_quote(path)),
+                Shell::Fish => format!("{} {name}", fish_quote(path)),
+                Shell::PowerShell => format!("& {} {name}", powershell_quote(path)),
+                Shell::Nushell => format!("^{} {name}", double_quote(path)),
+                Shell::Elvish => format!("(external {}) {name}", powershell_quote(path)), // Elvish quotes the same way as PowerShell
+                Shell::Xonsh => format!("{}, \"{name}\"", double_quote(path)),
+            },
+            Invoke::Env {
+                var,
+                completer_path: path,
+            } => match shell {
+                Shell::Bash | Shell::Zsh => format!("{var}={name} {} --", posix_quote(path)),
+                Shell::Fish => format!("{var}={name} {} --", fish_quote(path)),
+                Shell::PowerShell => format!(
+                    "& {{ $env:{var} = '{name}'; try {{ & {} -- @args }} finally {{ Remove-Item Env:\\{var} }} }}",
+                    powershell_quote(path)
+                ),
+                Shell::Nushell => format!("{var}={name} ^{} --", double_quote(path)),
+                Shell::Elvish => {
+                    format!("(external env) {var}={name} {} --", powershell_quote(path))
+                }
+                Shell::Xonsh => {
+                    format!("\"env\", \"{var}={name}\", {}, \"--\"", double_quote(path))
+                }
+            },
+        }
+    }
+}
+
+/// Write the registration script of `shell` to `w`.
+///
+/// - `bin_name` is the command to complete, e.g. `git`. It should be a plain command name.
+/// - `completer_path` is the path to the binary that does the completion.
+///   It will be quoted properly, so it's fine to have white space or quotes in it.
+///
+/// The zsh script can be sourced, or saved as `_<bin_name>` in a directory of `$fpath` for compinit to autoload.
+pub fn render(
+    shell: Shell,
+    bin_name: &str,
+    completer_path: &str,
+    w: &mut impl Write,
+) -> IoResult<()> {
+    render_with(shell, bin_name, Invoke::Positional(completer_path), w)
+}
+
+pub(crate) fn render_with(
+    shell: Shell,
+    bin_name: &str,
+    invoke: Invoke<'_>,
+    w: &mut impl Write,
+) -> IoResult<()> {
+    let template = match shell {
+        Shell::Bash => BASH,
+        Shell::Zsh => ZSH,
+        Shell::Fish => FISH,
+        Shell::PowerShell => POWERSHELL,
+        Shell::Nushell => NUSHELL,
+        Shell::Elvish => ELVISH,
+        Shell::Xonsh => XONSH,
+    };
+    let script = template
+        .replace("{fn}", &to_fn_name(bin_name))
+        .replace("{bin_py}", &double_quote(bin_name))
+        .replace("{bin}", bin_name)
+        .replace("{invoke}", &invoke.to_prefix(shell));
+    write!(w, "{script}")
+}
+
+/// Make a name that can be part of a function name in every shell.
+fn to_fn_name(bin_name: &str) -> String {
+    bin_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// `'it'\''s'` for bash and zsh
+fn posix_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+/// `'it\'s'` for fish
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+/// `'it''s'` for PowerShell and Elvish
+fn powershell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+/// `"it's \"quoted\""` for Nushell and Python (Xonsh)
+fn double_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render_str(shell: Shell, bin_name: &str, completer_path: &str) -> String {
+        let mut out = vec![];
+        render(shell, bin_name, completer_path, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(posix_quote("/it's/bin"), r#"'/it'\''s/bin'"#);
+        assert_eq!(fish_quote(r"/it's\bin"), r#"'/it\'s\\bin'"#);
+        assert_eq!(powershell_quote("/it's/bin"), "'/it''s/bin'");
+        assert_eq!(double_quote(r#"/"it's"\bin"#), r#""/\"it's\"\\bin""#);
+    }
+
+    #[test]
+    fn test_render() {
+        let script = render_str(Shell::Bash, "my-git", "/usr/bin/my git");
+        assert!(script.contains("_supplement_my_git()"));
+        assert!(script.contains("complete -F _supplement_my_git my-git"));
+        assert!(script.contains("out=$('/usr/bin/my git' bash "));
+
+        let script = render_str(Shell::Fish, "my-git", "/usr/bin/my git");
+        assert!(script.contains("'/usr/bin/my git' fish $words"));
+        assert!(script.contains("complete -k -c my-git -x -a \"(__supplement_my_git)\""));
+
+        let script = render_str(Shell::Xonsh, "my-git", "/usr/bin/my git");
+        assert!(script.contains("[\"/usr/bin/my git\", \"xonsh\", *words]"));
+        assert!(script.contains("add_one_completer(\"my-git\", _supplement_my_git, \"start\")"));
+
+        let invoke = Invoke::Env {
+            var: "COMPLETE",
+            completer_path: "/usr/bin/my git",
+        };
+        let mut out = vec![];
+        render_with(Shell::Zsh, "my-git", invoke, &mut out).unwrap();
+        let script = String::from_utf8(out).unwrap();
+        assert!(script.contains("out=\"$(COMPLETE=zsh '/usr/bin/my git' -- \"${(@Q)"));
+        assert!(script.contains("[[ \"$funcstack[1]\" = \"_my-git\" ]]"));
+        assert!(script.contains("compdef _my-git my-git"));
+
+        for shell in [
+            Shell::Bash,
+            Shell::Zsh,
+            Shell::Fish,
+            Shell::PowerShell,
+            Shell::Nushell,
+            Shell::Elvish,
+            Shell::Xonsh,
+        ] {
+            let script = render_str(shell, "qit", "/bin/qit");
+            assert!(!script.contains("{fn}"), "{shell:?}");
+            assert!(!script.contains("{bin}"), "{shell:?}");
+            assert!(!script.contains("{invoke}"), "{shell:?}");
+        }
+    }
+
+    #[test]
+    fn test_examples_up_to_date() {
+        let examples = [
+            (Shell::Bash, include_str!("../../examples/shell/qit.bash")),
+            (Shell::Zsh, include_str!("../../examples/shell/qit.zsh")),
+            (Shell::Fish, include_str!("../../examples/shell/qit.fish")),
+            (
+                Shell::PowerShell,
+                include_str!("../../examples/shell/qit.ps1"),
+            ),
+            (Shell::Nushell, include_str!("../../examples/shell/qit.nu")),
+            (Shell::Elvish, include_str!("../../examples/shell/qit.elv")),
+            (Shell::Xonsh, include_str!("../../examples/shell/qit.xsh")),
+        ];
+        for (shell, example) in examples {
+            // `install.sh` replaces the placeholder with the real path
+            let script = render_str(shell, "qit", "PLACEHOLDER_FOR_BIN_PATH");
+            assert_eq!(script, example, "{shell:?}");
+        }
+    }
+}
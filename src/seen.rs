@@ -8,6 +8,9 @@ use crate::id;
 pub struct SeenUnitNoVal {
     pub id: id::NoVal,
     pub count: u32,
+    /// Turned off by the other flag of its negation pair later, e.g. `--color --no-color`.
+    /// The `count` is reset to 0.
+    pub negated: bool,
 }
 #[derive(Debug, Eq, PartialEq)]
 pub struct SeenUnitSingleVal {
@@ -27,6 +30,14 @@ pub enum SeenUnit {
     Multi(SeenUnitMultiVal),
 }
 
+/// The net state of a boolean flag which can be negated, e.g. `--color` and `--no-color`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FlagState {
+    Unset,
+    Enabled,
+    Disabled,
+}
+
 pub trait Getter {
     type Ret;
     fn match_and_cast<'a>(&self, h: &'a SeenUnit) -> Option<&'a Self::Ret>;
@@ -125,13 +136,43 @@ impl Seen {
             match h {
                 SeenUnit::No(h) if h.id == id => {
                     h.count += 1;
+                    h.negated = false;
                     return;
                 }
                 _ => (),
             }
         }
 
-        self.0.push(SeenUnit::No(SeenUnitNoVal { id, count: 1 }));
+        self.0.push(SeenUnit::No(SeenUnitNoVal {
+            id,
+            count: 1,
+            negated: false,
+        }));
+    }
+    pub(crate) fn push_negated(&mut self, id: id::NoVal) {
+        log::debug!("push negated {:?}", id);
+        for h in self.0.iter_mut() {
+            match h {
+                SeenUnit::No(h) if h.id == id => {
+                    h.count = 0;
+                    h.negated = true;
+                    return;
+                }
+                _ => (),
+            }
+        }
+
+        self.0.push(SeenUnit::No(SeenUnitNoVal {
+            id,
+            count: 0,
+            negated: true,
+        }));
+    }
+    /// Like [`Seen::push_negated`], but only if `id` is already seen.
+    pub(crate) fn turn_off(&mut self, id: id::NoVal) {
+        if self.find(id).is_some() {
+            self.push_negated(id);
+        }
     }
     pub(crate) fn push_single_val(&mut self, id: id::SingleVal, value: String) {
         log::debug!("push single val {:?} {}", id, value);
@@ -214,6 +255,30 @@ impl Seen {
         None
     }
 
+    /// Whether `id` is present, so a negated flag doesn't count, e.g. `--color` after `--no-color`.
+    pub(crate) fn contains(&self, id: id::Any) -> bool {
+        match id {
+            id::Any::No(id) => self.find(id).is_some_and(|h| !h.negated),
+            id::Any::Single(id) => self.find(id).is_some(),
+            id::Any::Multi(id) => self.find(id).is_some(),
+        }
+    }
+
+    /// The net state of a boolean flag, where the last one of `--x` and `--no-x` wins.
+    ///
+    /// ```
+    /// use supplement::{Seen, id, seen::FlagState};
+    /// let seen = Seen::new();
+    /// assert_eq!(seen.flag_state(id::NoVal::new(0)), FlagState::Unset);
+    /// ```
+    pub fn flag_state(&self, id: id::NoVal) -> FlagState {
+        match self.find(id) {
+            None => FlagState::Unset,
+            Some(h) if h.negated => FlagState::Disabled,
+            Some(_) => FlagState::Enabled,
+        }
+    }
+
     #[doc(hidden)]
     pub fn into_inner(self) -> Vec<SeenUnit> {
         self.0
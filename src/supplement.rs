@@ -1,4 +1,4 @@
-use crate::abstraction::{Arg as AbsArg, Command as AbsCommand};
+use crate::abstraction::{Arg as AbsArg, Command as AbsCommand, Relations, Requirement};
 use crate::clap::CommandFactory;
 use crate::gen_prelude::*;
 use crate::{CompletionGroup, Result, id};
@@ -82,11 +82,18 @@ pub trait Supplement: CommandFactory {
     fn id_from_cmd(cmd: &[impl AsRef<str>]) -> Option<(Option<Self::ID>, u32)>;
 
     /// Refer to document of [`Supplement`].
+    /// The flags and subcommands hidden in clap are completed with [`Hidden::Prefixed`].
     fn gen_cmd() -> Command<Self::ID> {
+        Self::gen_cmd_with(Hidden::Prefixed)
+    }
+
+    /// Like [`Supplement::gen_cmd`], but decide how the flags and subcommands hidden in clap
+    /// (i.e. `hide(true)`) are completed, the same as [`crate::generate::Config::hidden`].
+    fn gen_cmd_with(hidden: Hidden) -> Command<Self::ID> {
         let mut cmd = Self::command();
         cmd.build();
         let cmd = AbsCommand(&cmd);
-        gen_cmd_inner::<Self>(true, &cmd, &[], &mut vec![])
+        gen_cmd_inner::<Self>(true, &cmd, hidden, &[], &mut vec![])
     }
 
     /// Shorthand for [`Supplement::gen_cmd`] + [`Command::supplement`].
@@ -95,6 +102,15 @@ pub trait Supplement: CommandFactory {
         let cmd = Self::gen_cmd();
         cmd.supplement(args)
     }
+
+    /// Shorthand for [`Supplement::gen_cmd`] + [`Command::supplement_at`].
+    fn supplement_at(
+        args: impl Iterator<Item = String>,
+        cursor: usize,
+    ) -> Result<(Seen, CompletionGroup<Self::ID>)> {
+        let cmd = Self::gen_cmd();
+        cmd.supplement_at(args, cursor)
+    }
 }
 
 #[derive(Clone)]
@@ -116,9 +132,63 @@ fn get_id(arg: &AbsArg<'_>) -> String {
     }
 }
 
+/// The seen ID of another arg in the command, to refer to it. `trace` is the path of the command.
+fn gen_other_id<Root: Supplement>(other: &AbsArg<'_>, trace: &[String]) -> id::Any {
+    let mut trace = trace.to_vec();
+    trace.push(get_id(other));
+    let (_, seen_id) = Root::id_from_cmd(&trace).unwrap_or_else(|| panic!("{trace:?} not found"));
+    if !other.takes_values() {
+        id::NoVal::new(seen_id).any()
+    } else if other.get_max_num_args() > 1 || other.get_value_delimiter().is_some() {
+        id::MultiVal::new(seen_id).any()
+    } else {
+        id::SingleVal::new(seen_id).any()
+    }
+}
+
+/// The seen IDs of the args in conflict with `arg`, see [`AbsCommand::get_conflicts`].
+/// `trace` is the path of the command.
+fn gen_conflicts<Root: Supplement>(
+    arg: &AbsArg<'_>,
+    relations: &Relations<'_>,
+    trace: &[String],
+) -> Vec<id::Any> {
+    let Some(conflicts) = relations.conflicts.get(arg.get_id()) else {
+        return vec![];
+    };
+    conflicts
+        .iter()
+        .map(|other| gen_other_id::<Root>(other, trace))
+        .collect()
+}
+
+/// When `arg` is required, see [`AbsCommand::get_requirements`]. `trace` is the path of the command.
+fn gen_required<Root: Supplement>(
+    arg: &AbsArg<'_>,
+    relations: &Relations<'_>,
+    trace: &[String],
+) -> Vec<Required> {
+    let required = match relations.requirements.get(arg.get_id()) {
+        None => return vec![],
+        Some(Requirement::Always) => Required::Always,
+        Some(Requirement::UnlessPresent(others)) => {
+            let ids = others
+                .iter()
+                .map(|other| gen_other_id::<Root>(other, trace));
+            Required::UnlessPresent(CowSlice::Owned(ids.collect()))
+        }
+    };
+    vec![required]
+}
+
+fn gen_hidden(hidden: Hidden, is_hide_set: bool) -> Hidden {
+    if is_hide_set { hidden } else { Hidden::No }
+}
+
 fn gen_cmd_inner<Root: Supplement>(
     first: bool,
     cmd: &AbsCommand<'_>,
+    hidden: Hidden,
     trace: &[String],
     global_flags: &mut Vec<GlobalFlag<Root::ID>>,
 ) -> Command<Root::ID> {
@@ -132,6 +202,7 @@ fn gen_cmd_inner<Root: Supplement>(
     let custom_help_cmd = cmd.is_disable_help_subcommand_set();
     let description = Cow::Owned(cmd.get_about().map(|s| s.to_string()).unwrap_or_default());
 
+    let relations = cmd.get_relations();
     let flags: Vec<Flag<Root::ID>> = cmd
         .get_arguments()
         .filter(|a| !a.is_positional())
@@ -143,19 +214,19 @@ fn gen_cmd_inner<Root: Supplement>(
             let id: &str = a.get_id().as_ref();
             custom_version_flag || id != "version"
         })
-        .map(|arg| gen_flag::<Root>(arg, &trace, global_flags))
+        .map(|arg| gen_flag::<Root>(cmd, arg, &relations, hidden, &trace, global_flags))
         .collect();
 
     let mut args: Vec<Arg<Root::ID>> = cmd
         .get_arguments()
         .filter(|a| a.is_positional())
-        .map(|arg| gen_arg::<Root>(arg, &trace))
+        .map(|arg| gen_arg::<Root>(arg, &relations, &trace))
         .collect();
 
     let commands: Vec<Command<Root::ID>> = cmd
         .get_subcommands()
         .filter(|c| custom_help_cmd || c.get_name() != "help")
-        .map(|sub| gen_cmd_inner::<Root>(false, &sub, &trace, global_flags))
+        .map(|sub| gen_cmd_inner::<Root>(false, &sub, hidden, &trace, global_flags))
         .collect();
 
     if cmd.is_allow_external_subcommands_set() {
@@ -166,21 +237,38 @@ fn gen_cmd_inner<Root: Supplement>(
             id,
             seen_id: id::MultiVal::new(seen_id).into(),
             max_values: usize::MAX,
+            last: false,
+            value_delimiter: None,
             possible_values: CowOwned::Borrow(&[]),
+            conflicts: CowSlice::Borrow(&[]),
+            required: CowSlice::Borrow(&[]),
         })
     }
 
+    let aliases = cmd
+        .get_all_aliases()
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect();
+
     Command {
         name,
         description,
+        aliases: CowOwned::Owned(aliases),
+        hidden: gen_hidden(hidden, cmd.is_hide_set()),
         all_flags: CowSlice::Owned(flags),
         args: CowSlice::Owned(args),
         commands: CowSlice::Owned(commands),
+        infer_long_args: cmd.is_infer_long_args_set(),
+        infer_subcommands: cmd.is_infer_subcommands_set(),
     }
 }
 
 fn gen_flag<Root: Supplement>(
+    cmd: &AbsCommand<'_>,
     arg: AbsArg<'_>,
+    relations: &Relations<'_>,
+    hidden: Hidden,
     trace: &[String],
     global_flags: &mut Vec<GlobalFlag<Root::ID>>,
 ) -> Flag<Root::ID> {
@@ -215,12 +303,23 @@ fn gen_flag<Root: Supplement>(
         .into_iter()
         .map(|s| s.to_string())
         .collect();
+    let hidden_shorts = arg.get_hidden_short_aliases();
+    let hidden_longs: Vec<String> = arg
+        .get_hidden_aliases()
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect();
     let description = Cow::Owned(arg.get_help());
 
     let takes_values = arg.takes_values();
 
     // TODO: re-implement `generate/mod.rs`: `let (once, ty) = match flag.get_action() ...`
-    let once = !arg.is_global_set();
+    let negation = relations.negations.get(arg.get_id());
+    let once = !arg.is_global_set()
+        && !negation.as_ref().is_some_and(|n| n.overridable)
+        && !cmd.is_args_override_self();
+    let conflicts = gen_conflicts::<Root>(&arg, relations, &trace[..trace.len() - 1]);
+    let required = gen_required::<Root>(&arg, relations, &trace[..trace.len() - 1]);
 
     let ty = if takes_values {
         let possible_values: Vec<(String, String)> = arg
@@ -245,19 +344,42 @@ fn gen_flag<Root: Supplement>(
             CompleteWithEqual::NoNeed
         };
 
+        let min = arg.get_min_num_args();
         let max = arg.get_max_num_args();
-        let seen_id = if max > 1 {
+        let value_delimiter = arg.get_value_delimiter();
+        let seen_id = if max > 1 || value_delimiter.is_some() {
             id::MultiVal::new(seen_id).into()
         } else {
             id::SingleVal::new(seen_id).into()
         };
 
-        flag_type::Type::new_valued(
+        flag_type::Type::new_multi_valued(
             id,
             seen_id,
             complete_with_equal,
             CowOwned::Owned(possible_values),
+            min,
+            max,
+            value_delimiter,
         )
+    } else if let Some(negation) = negation {
+        // Turn off the other flag of the pair
+        let is_negative = negation.negative.get_id() == arg.get_id();
+        let other = if is_negative {
+            negation.positive
+        } else {
+            negation.negative
+        };
+        let mut trace = trace.clone();
+        *trace.last_mut().unwrap() = get_id(&other);
+        let (_, other_id) =
+            Root::id_from_cmd(&trace).unwrap_or_else(|| panic!("{trace:?} not found"));
+        let (seen_id, other_id) = (id::NoVal::new(seen_id), id::NoVal::new(other_id));
+        if is_negative {
+            flag_type::Type::new_negation(seen_id, other_id)
+        } else {
+            flag_type::Type::new_negatable(seen_id, other_id)
+        }
     } else {
         // TODO: this ID is still generated?
         flag_type::Type::new_bool(id::NoVal::new(seen_id))
@@ -267,17 +389,29 @@ fn gen_flag<Root: Supplement>(
         ty,
         short: CowSlice::Owned(shorts),
         long: CowOwned::Owned(longs),
+        hidden_short: CowSlice::Owned(hidden_shorts),
+        hidden_long: CowOwned::Owned(hidden_longs),
+        hidden: gen_hidden(hidden, arg.is_hide_set()),
         description,
         once,
+        conflicts: CowSlice::Owned(conflicts),
+        required: CowSlice::Owned(required),
     }
 }
 
-fn gen_arg<Root: Supplement>(arg: AbsArg<'_>, trace: &[String]) -> Arg<Root::ID> {
+fn gen_arg<Root: Supplement>(
+    arg: AbsArg<'_>,
+    relations: &Relations<'_>,
+    trace: &[String],
+) -> Arg<Root::ID> {
+    let conflicts = gen_conflicts::<Root>(&arg, relations, trace);
+    let required = gen_required::<Root>(&arg, relations, trace);
     let name = get_id(&arg);
     let mut trace = trace.to_vec();
     trace.push(name.to_string());
 
     let max_values = arg.get_max_num_args();
+    let value_delimiter = arg.get_value_delimiter();
 
     let (id, seen_id) = Root::id_from_cmd(&trace).unwrap_or_else(|| panic!("{trace:?} not found"));
 
@@ -292,7 +426,7 @@ fn gen_arg<Root: Supplement>(arg: AbsArg<'_>, trace: &[String]) -> Arg<Root::ID>
         })
         .collect();
 
-    let seen_id = if max_values > 1 {
+    let seen_id = if max_values > 1 || value_delimiter.is_some() {
         id::MultiVal::new(seen_id).into()
     } else {
         id::SingleVal::new(seen_id).into()
@@ -302,6 +436,10 @@ fn gen_arg<Root: Supplement>(arg: AbsArg<'_>, trace: &[String]) -> Arg<Root::ID>
         id,
         seen_id,
         max_values,
+        last: arg.is_last_set(),
+        value_delimiter,
         possible_values: CowOwned::Owned(possible_values),
+        conflicts: CowSlice::Owned(conflicts),
+        required: CowSlice::Owned(required),
     }
 }
@@ -0,0 +1,364 @@
+//! The module to split a raw command-line into words, following the quoting rules of each [`Shell`].
+//!
+//! Useful when the shell hands over the whole command-line as a string (e.g. PowerShell and Xonsh),
+//! or when you want to know if the user is typing inside quotes.
+//!
+//! ```
+//! use supplement::Shell;
+//! use supplement::tokenize::{Quote, tokenize};
+//!
+//! let line = r#"git commit -m "fix the"#;
+//! let tokens = tokenize(Shell::Bash, line, line.len());
+//! assert_eq!(tokens.words, vec!["git", "commit", "-m", "fix the"]);
+//! assert_eq!(tokens.cursor, 3);
+//! assert_eq!(tokens.quote, Quote::Double);
+//! ```
+
+use crate::Shell;
+
+/// The quoting state at the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quote {
+    /// Not in quotes, e.g. `git checkout ma|`
+    #[default]
+    None,
+    /// In an unterminated single quote, e.g. `git checkout 'ma|`
+    Single,
+    /// In an unterminated double quote, e.g. `git checkout "ma|`
+    Double,
+}
+
+/// The result of [`tokenize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tokenized {
+    /// The words with quotes and escapes resolved. The program's name is included.
+    ///
+    /// The word under cursor is cut off at the cursor, and it's always present, even if it's empty.
+    pub words: Vec<String>,
+    /// The index of the word under cursor in `words`,
+    /// which can be fed into [`crate::core::Command::supplement_at`] together with `words`.
+    pub cursor: usize,
+    /// The quoting state at the cursor.
+    pub quote: Quote,
+}
+
+/// How a shell escapes and quotes.
+struct Rules {
+    /// The escape character outside quotes.
+    escape: Option<char>,
+    /// `'it''s'`
+    single_doubled: bool,
+    /// `'it\'s'`
+    single_escape: bool,
+    /// The escape character in double quotes, and the characters it can escape.
+    /// `None` means all characters, C-like sequences such as `\n` included.
+    double_escape: Option<(char, Option<&'static str>)>,
+    /// `"say ""hi"""`
+    double_doubled: bool,
+}
+
+impl Rules {
+    fn of(shell: Shell) -> Self {
+        match shell {
+            Shell::Bash | Shell::Zsh => Rules {
+                escape: Some('\\'),
+                single_doubled: false,
+                single_escape: false,
+                double_escape: Some(('\\', Some("$`\"\\\n"))),
+                double_doubled: false,
+            },
+            Shell::Fish => Rules {
+                escape: Some('\\'),
+                single_doubled: false,
+                single_escape: true,
+                double_escape: Some(('\\', Some("$\"\\\n"))),
+                double_doubled: false,
+            },
+            Shell::PowerShell => Rules {
+                escape: Some('`'),
+                single_doubled: true,
+                single_escape: false,
+                double_escape: Some(('`', None)),
+                double_doubled: true,
+            },
+            Shell::Nushell => Rules {
+                escape: None,
+                single_doubled: false,
+                single_escape: false,
+                double_escape: Some(('\\', None)),
+                double_doubled: false,
+            },
+            Shell::Elvish => Rules {
+                escape: None,
+                single_doubled: true,
+                single_escape: false,
+                double_escape: Some(('\\', None)),
+                double_doubled: false,
+            },
+            Shell::Xonsh => Rules {
+                escape: Some('\\'),
+                single_doubled: false,
+                single_escape: true,
+                double_escape: Some(('\\', None)),
+                double_doubled: false,
+            },
+        }
+    }
+}
+
+/// `\n` in `"a\nb"`
+fn unescape_seq(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        _ => c,
+    }
+}
+
+/// Split `line` into words according to the quoting rules of `shell`.
+///
+/// `cursor` is the byte offset of the cursor in `line`, usually `line.len()`.
+/// Words after the cursor are also included, while the rest of the word under cursor is dropped,
+/// e.g. `git chec|kout main` results in `["git", "chec", "main"]`.
+///
+/// This is a best effort: shell features such as variables and globs are not expanded.
+pub fn tokenize(shell: Shell, line: &str, cursor: usize) -> Tokenized {
+    let rules = Rules::of(shell);
+
+    let mut words = vec![];
+    let mut cur = String::new();
+    let mut in_word = false;
+    // The rest of the word under cursor goes nowhere
+    let mut discard = false;
+    let mut quote = Quote::None;
+    let mut escaped = false;
+    let mut cursor_info = None;
+
+    let mut chars = line.char_indices().peekable();
+    loop {
+        let next = chars.next();
+        if cursor_info.is_none() && next.is_none_or(|(i, _)| i >= cursor) {
+            cursor_info = Some((words.len(), quote));
+            words.push(std::mem::take(&mut cur));
+            discard = in_word || next.is_some_and(|(_, c)| !c.is_whitespace());
+            in_word = discard;
+        }
+        let Some((_, c)) = next else {
+            break;
+        };
+
+        if escaped {
+            escaped = false;
+            cur.push(c);
+            continue;
+        }
+        match quote {
+            Quote::None => {
+                if c.is_whitespace() {
+                    if in_word && !discard {
+                        words.push(std::mem::take(&mut cur));
+                    }
+                    cur.clear();
+                    in_word = false;
+                    discard = false;
+                    continue;
+                }
+                in_word = true;
+                match c {
+                    '\'' => quote = Quote::Single,
+                    '"' => quote = Quote::Double,
+                    _ if Some(c) == rules.escape => escaped = true,
+                    _ => cur.push(c),
+                }
+            }
+            Quote::Single => match c {
+                '\'' if rules.single_doubled && chars.next_if(|(_, c)| *c == '\'').is_some() => {
+                    cur.push('\'');
+                }
+                '\'' => quote = Quote::None,
+                '\\' if rules.single_escape => match chars.next_if(|(_, c)| "'\\".contains(*c)) {
+                    Some((_, c)) => cur.push(c),
+                    None => cur.push('\\'),
+                },
+                _ => cur.push(c),
+            },
+            Quote::Double => match c {
+                '"' if rules.double_doubled && chars.next_if(|(_, c)| *c == '"').is_some() => {
+                    cur.push('"');
+                }
+                '"' => quote = Quote::None,
+                _ => match rules.double_escape {
+                    Some((escape, escapable)) if c == escape => {
+                        let next = chars.next_if(|(_, c)| escapable.is_none_or(|e| e.contains(*c)));
+                        match (next, escapable) {
+                            (Some((_, c)), None) => cur.push(unescape_seq(c)),
+                            (Some((_, c)), Some(_)) => cur.push(c),
+                            (None, _) => cur.push(escape),
+                        }
+                    }
+                    _ => cur.push(c),
+                },
+            },
+        }
+    }
+    if in_word && !discard {
+        words.push(cur);
+    }
+
+    let (cursor, quote) = cursor_info.unwrap();
+    Tokenized {
+        words,
+        cursor,
+        quote,
+    }
+}
+
+/// Remove the quotes and escapes of a single word as typed, e.g. `"it's` for bash results in
+/// `it's` with [`Quote::Double`].
+///
+/// Useful when the shell passes the words as typed, such as `COMP_WORDS` in bash,
+/// or `commandline -ct` in fish.
+pub fn unquote(shell: Shell, word: &str) -> (String, Quote) {
+    let mut tokens = tokenize(shell, word, word.len());
+    (tokens.words.swap_remove(tokens.cursor), tokens.quote)
+}
+
+/// [`unquote`] the words passed by the registration scripts in place, and return the [`Quote`] of the last one,
+/// which should be fed into [`crate::completion::Ready::quote`].
+///
+/// Bash passes all words as typed, and fish passes the word under cursor as typed.
+/// The other shells pass the words already unquoted.
+pub fn unquote_args(shell: Shell, args: &mut [String]) -> Quote {
+    let raw_start = match shell {
+        Shell::Bash => 0,
+        Shell::Fish => args.len().saturating_sub(1),
+        _ => args.len(),
+    };
+    let mut quote = Quote::None;
+    for arg in args[raw_start..].iter_mut() {
+        (*arg, quote) = unquote(shell, arg);
+    }
+    quote
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tokens(shell: Shell, line: &str) -> (Vec<String>, usize, Quote) {
+        let cursor = line.find('|').unwrap_or(line.len());
+        let line = line.replacen('|', "", 1);
+        let t = tokenize(shell, &line, cursor);
+        (t.words, t.cursor, t.quote)
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(
+            tokens(Shell::Bash, "git  log "),
+            (
+                vec!["git".to_owned(), "log".to_owned(), "".to_owned()],
+                2,
+                Quote::None
+            )
+        );
+        assert_eq!(
+            tokens(Shell::Bash, "git lo"),
+            (vec!["git".to_owned(), "lo".to_owned()], 1, Quote::None)
+        );
+        assert_eq!(
+            tokens(Shell::Bash, "git chec|kout main"),
+            (
+                vec!["git".to_owned(), "chec".to_owned(), "main".to_owned()],
+                1,
+                Quote::None
+            )
+        );
+        assert_eq!(
+            tokens(Shell::Bash, "git | main"),
+            (
+                vec!["git".to_owned(), "".to_owned(), "main".to_owned()],
+                1,
+                Quote::None
+            )
+        );
+        assert_eq!(
+            tokens(Shell::Bash, "git |main"),
+            (vec!["git".to_owned(), "".to_owned()], 1, Quote::None)
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        let check = |shell: Shell, line: &str, word: &str, quote: Quote| {
+            let (words, cursor, q) = tokens(shell, line);
+            assert_eq!(
+                (words[cursor].as_str(), q),
+                (word, quote),
+                "{shell:?} {line}"
+            );
+        };
+
+        check(Shell::Bash, r"cmd a\ b", "a b", Quote::None);
+        check(Shell::Bash, r"cmd 'a\ b", r"a\ b", Quote::Single);
+        check(Shell::Bash, r#"cmd "a\"\b"#, r#"a"\b"#, Quote::Double);
+        check(Shell::Bash, r#"cmd "a b"c"#, "a bc", Quote::None);
+        check(Shell::Bash, r#"cmd 'it'\''s"#, "it's", Quote::Single);
+        check(Shell::Zsh, r#"cmd "a'b"#, "a'b", Quote::Double);
+
+        check(Shell::Fish, r"cmd 'it\'s\\\n", r"it's\\n", Quote::Single);
+
+        check(Shell::PowerShell, r"cmd 'it''s", "it's", Quote::Single);
+        check(
+            Shell::PowerShell,
+            r#"cmd "say ""hi`""#,
+            r#"say "hi""#,
+            Quote::Double,
+        );
+        check(Shell::PowerShell, r"cmd C:\a` b", r"C:\a b", Quote::None);
+
+        check(Shell::Nushell, r"cmd C:\a", r"C:\a", Quote::None);
+        check(Shell::Nushell, r#"cmd "a\tb"#, "a\tb", Quote::Double);
+
+        check(Shell::Elvish, r"cmd 'it''s", "it's", Quote::Single);
+        check(Shell::Xonsh, r"cmd 'it\'s", "it's", Quote::Single);
+    }
+
+    #[test]
+    fn test_unquote() {
+        assert_eq!(
+            unquote(Shell::Bash, r#""it's"#),
+            ("it's".to_owned(), Quote::Double)
+        );
+        assert_eq!(
+            unquote(Shell::Bash, r"a\ b"),
+            ("a b".to_owned(), Quote::None)
+        );
+        assert_eq!(unquote(Shell::Fish, ""), ("".to_owned(), Quote::None));
+    }
+
+    #[test]
+    fn test_unquote_args() {
+        let check = |shell: Shell, args: &[&str], expected: &[&str], quote: Quote| {
+            let mut args: Vec<_> = args.iter().map(|s| s.to_string()).collect();
+            assert_eq!(unquote_args(shell, &mut args), quote);
+            assert_eq!(args, expected);
+        };
+        check(
+            Shell::Bash,
+            &["git", r"a\ b", "'ma"],
+            &["git", "a b", "ma"],
+            Quote::Single,
+        );
+        check(
+            Shell::Fish,
+            &["git", r"a\ b", "\"ma"],
+            &["git", r"a\ b", "ma"],
+            Quote::Double,
+        );
+        check(Shell::Zsh, &["git", "'ma"], &["git", "'ma"], Quote::None);
+        check(Shell::Fish, &[], &[], Quote::None);
+    }
+}
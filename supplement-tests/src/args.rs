@@ -1,4 +1,4 @@
-use clap::{Parser, ValueEnum};
+use clap::{ArgGroup, Parser, ValueEnum};
 
 #[derive(Parser, Debug)]
 pub struct Arg {
@@ -24,8 +24,9 @@ pub enum SubCommand {
         #[clap(long, num_args = 0..=1, default_value = None, default_missing_value = "full", require_equals = true)]
         pretty: Option<Pretty>,
     },
+    #[clap(alias = "co")]
     Checkout {
-        #[clap(short, help = "Create new branch")]
+        #[clap(short, alias = "new-branch", help = "Create new branch")]
         b: bool,
         #[clap(long)]
         flag1: Option<String>, // ignored
@@ -36,6 +37,18 @@ pub enum SubCommand {
     Log {
         #[clap(long)]
         graph: bool,
+        #[clap(long, overrides_with = "no_color")]
+        color: bool,
+        #[clap(long, overrides_with = "color")]
+        no_color: bool,
+        #[clap(long, group = "format")]
+        json: bool,
+        #[clap(long, group = "format")]
+        yaml: bool,
+        #[clap(long, conflicts_with = "graph")]
+        stat: bool,
+        #[clap(long, overrides_with = "abbrev")]
+        abbrev: bool,
         #[clap(long, num_args = 0..=1, default_value = None, default_missing_value = "full", require_equals = true)]
         pretty: Option<Pretty>,
         commit: Option<String>,
@@ -44,6 +57,8 @@ pub enum SubCommand {
         flag1: Option<String>,
         #[clap(long)]
         flag2: bool, // ignored
+        #[clap(long, hide = true)]
+        debug: bool,
     },
     #[clap(about = "log")]
     IgnoredCmd {
@@ -55,6 +70,12 @@ pub enum SubCommand {
         sub: Option<Remote>,
     },
 
+    #[clap(hide = true)]
+    Fsck {
+        #[clap(long)]
+        full: bool,
+    },
+
     #[clap(external_subcommand)]
     Other(#[allow(unused)] Vec<String>),
 }
@@ -70,9 +91,14 @@ pub enum Pretty {
 
 #[derive(Parser, Debug)]
 pub enum Remote {
+    #[clap(group(ArgGroup::new("branch").required(true).args(["master", "track"])))]
     Add {
         #[clap(long)]
         tags: bool,
+        #[clap(long)]
+        master: Option<String>,
+        #[clap(long)]
+        track: Option<String>,
         name: String,
     },
     Remove,
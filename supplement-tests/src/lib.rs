@@ -31,7 +31,7 @@ pub fn map_unready<ID: Debug + Copy>(grp: &CompletionGroup<ID>) -> (ID, (&str, V
 mod test {
     use supplement::generate::{Config, generate};
     mod def {
-        #![allow(clippy::needless_lifetimes)]
+        #![allow(clippy::needless_lifetimes, clippy::enum_variant_names)]
         include!("def.rs");
     }
 
@@ -68,6 +68,19 @@ mod test {
         do_assrt(err);
     }
     #[test]
+    fn test_gen_hidden() {
+        use crate::args::Arg;
+        use clap::CommandFactory;
+        use supplement::core::Hidden;
+
+        let mut s: Vec<u8> = vec![];
+        let cfg = Config::new().hidden(Hidden::Always);
+        generate(&mut Arg::command(), cfg, &mut s).unwrap();
+        let s = String::from_utf8(s).unwrap();
+        assert_eq!(s.matches("hidden: Hidden::Always").count(), 2);
+        assert!(!s.contains("hidden: Hidden::Prefixed"));
+    }
+    #[test]
     fn test_gen_custom() {
         use crate::args::Arg;
         use clap::CommandFactory;
@@ -107,7 +120,19 @@ mod test {
 
         let comps = run("git log -").unwrap();
         assert_eq!(
-            vec!["--flag1", "--git-dir", "--graph", "--pretty", "--pretty="],
+            vec![
+                "--abbrev",
+                "--color",
+                "--flag1",
+                "--git-dir",
+                "--graph",
+                "--json",
+                "--no-color",
+                "--pretty",
+                "--pretty=",
+                "--stat",
+                "--yaml"
+            ],
             map_ready(&comps)
         );
 
@@ -126,7 +151,106 @@ mod test {
 
         // test ignoring global flags
         let comps = run("git remote add -").unwrap();
-        assert_eq!(vec!["--tags"], map_ready(&comps));
+        assert_eq!(vec!["--master", "--tags", "--track"], map_ready(&comps));
+    }
+
+    #[test]
+    fn test_alias() {
+        let comps = run("git co -").unwrap();
+        assert_eq!(vec!["--git-dir", "-b"], map_ready(&comps));
+
+        let (h, comps) = run_with_seen("git co --new-branch x").unwrap();
+        let (id, _) = map_unready(&comps);
+        match id.with_seen(&h) {
+            id!(def(_root) checkout(chk) file_or_commit) => assert_eq!(chk.val_b(), 1),
+            _ => panic!("id is {id:?}"),
+        }
+    }
+
+    #[test]
+    fn test_negation() {
+        // `--color` and `--no-color` override each other
+        let comps = run("git log --color --no-color --").unwrap();
+        let comps = map_ready(&comps);
+        assert!(comps.contains(&"--color") && comps.contains(&"--no-color"));
+
+        let (h, comps) = run_with_seen("git log --color --no-color x").unwrap();
+        let (id, _) = map_unready(&comps);
+        match id.with_seen(&h) {
+            id!(def(_root) log(log) commit) => assert_eq!(log.val_color(), 0),
+            _ => panic!("id is {id:?}"),
+        }
+        let (h, comps) = run_with_seen("git log --no-color --color x").unwrap();
+        let (id, _) = map_unready(&comps);
+        match id.with_seen(&h) {
+            id!(def(_root) log(log) commit) => assert_eq!(log.val_color(), 1),
+            _ => panic!("id is {id:?}"),
+        }
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let comps = run("git log --json --graph --abbrev --").unwrap();
+        assert_eq!(
+            vec![
+                "--color",
+                "--flag1",
+                "--git-dir",
+                "--no-color",
+                "--pretty",
+                "--pretty="
+            ],
+            map_ready(&comps)
+        );
+
+        let comps = run("git log --stat -").unwrap();
+        assert!(!map_ready(&comps).contains(&"--graph"));
+    }
+
+    #[test]
+    fn test_required() {
+        let required = |cmd: &str| -> Vec<String> {
+            let comps = run(cmd).unwrap();
+            let CompletionGroup::Ready(r) = comps else {
+                panic!("{comps:?} is unready");
+            };
+            let comps = r.inner().0.iter().filter(|c| c.priority > 0);
+            comps
+                .map(|c| format!("{} {}", c.value, c.description))
+                .collect()
+        };
+
+        // Any member of the required group will do
+        assert_eq!(
+            required("git remote add -"),
+            vec!["--master (required) master", "--track (required) track"]
+        );
+        assert!(required("git remote add --master m -").is_empty());
+        assert_eq!(required("git remote add --tags -").len(), 2);
+        // The group is exclusive as well
+        let comps = run("git remote add --master m -").unwrap();
+        assert!(!map_ready(&comps).contains(&"--track"));
+    }
+
+    #[test]
+    fn test_hidden() {
+        let comps = run("git f").unwrap();
+        let (_, (_, cmds, _)) = map_unready(&comps);
+        assert!(cmds.contains(&"fsck"));
+        let comps = run("git ").unwrap();
+        let (_, (_, cmds, _)) = map_unready(&comps);
+        assert!(!cmds.contains(&"fsck"));
+        let comps = run("git fsck -").unwrap();
+        assert_eq!(vec!["--full", "--git-dir"], map_ready(&comps));
+
+        let comps = run("git log --de").unwrap();
+        assert!(map_ready(&comps).contains(&"--debug"));
+        let (h, comps) = run_with_seen("git log --debug x").unwrap();
+        let (id, _) = map_unready(&comps);
+        match id.with_seen(&h) {
+            id!(def(_root) log(log) commit) => assert_eq!(log.val_debug(), 1),
+            _ => panic!("id is {id:?}"),
+        }
     }
 
     #[test]
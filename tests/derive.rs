@@ -26,6 +26,14 @@ pub struct TestFlat {
 #[derive(Parser, Debug, Clone, Supplement)]
 pub enum Sub {
     Log {
+        #[clap(long)]
+        color: bool,
+        #[clap(long, conflicts_with = "color")]
+        no_color: bool,
+        #[clap(long, conflicts_with = "pretty")]
+        oneline: bool,
+        #[clap(long, hide = true)]
+        debug: bool,
         #[clap(long, value_enum)]
         pretty: Option<Pretty>, // NOTE: the `value_enum` is necessary due to lack of specialization
         commit: Commit,
@@ -43,6 +51,8 @@ pub enum Sub {
 
     // To test the Pacal => camal logic
     CherryPick {
+        #[clap(long)]
+        mainline: u32,
         commit: String,
     },
 
@@ -50,6 +60,12 @@ pub enum Sub {
         paths: Vec<PathBuf>,
     },
 
+    Diff {
+        commit: Option<String>,
+        #[clap(last = true)]
+        paths: Vec<PathBuf>,
+    },
+
     #[clap(external_subcommand)]
     Other(Vec<String>),
 }
@@ -150,6 +166,11 @@ pub fn handle_id(seen: &Seen, id: <Git as Supplement>::ID) {
             let _: Option<Result<Commit, _>> = log_acc.commit(seen);
         }
         id!(GitID.sub SubID.RM.paths) => {}
+        id!(GitID.sub SubID.Diff.commit) => {}
+        id!(GitID.sub SubID.Diff.paths(diff_acc)) => {
+            let _: Vec<&Path> = diff_acc.paths(seen).collect();
+        }
+        id!(GitID.sub SubID.CherryPick.mainline) => {}
         id!(GitID.sub(acc) SubID.Log.commit) | id!(GitID.sub(acc) SubID.CherryPick.commit) => {
             let _: Option<&str> = acc.git_dir(seen);
         }
@@ -218,6 +239,171 @@ mod test {
         assert_eq!(Git::id_from_cmd(&["nonexistent"]), None);
     }
 
+    #[test]
+    fn test_complete_env() {
+        use supplement::{CompleteEnv, Completion};
+
+        let _ = env_logger::try_init();
+
+        fn run(shell: &str, args: &[&str]) -> Result<String, String> {
+            let args = args.iter().map(|s| s.to_string());
+            let mut out = vec![];
+            CompleteEnv::new("COMPLETE").run::<Git>(
+                shell,
+                args,
+                |_, id, value, unready| {
+                    assert!(matches!(id, id!(GitID.sub SubID.CherryPick.commit)));
+                    assert_eq!(value, "ma");
+                    unready.to_ready(vec![Completion::new("main", "")])
+                },
+                &mut out,
+            )?;
+            Ok(String::from_utf8(out).unwrap())
+        }
+
+        let out = run("fish", &["/bin/git", "--", "git", "cherry-pick", "ma"]).unwrap();
+        assert_eq!(out, "main\t\n");
+
+        let out = run("fish", &["/bin/git", "--", "git", "remote1", "a"]).unwrap();
+        assert_eq!(out, "add\tcommand\n");
+
+        // The raw words are unquoted, and the values are quoted accordingly
+        let out = run("fish", &["/bin/git", "--", "git", "cherry-pick", "'ma"]).unwrap();
+        assert_eq!(out, "main\t\n");
+        let out = run("bash", &["/bin/git", "--", "git", "cherry-pick", "\"ma"]).unwrap();
+        assert_eq!(out, "\"main\n");
+
+        let out = run("bash", &["/bin/git"]).unwrap();
+        assert!(out.contains("complete -F _supplement_git git"));
+        assert!(out.contains("COMPLETE=bash"));
+
+        assert!(run("unknown", &["/bin/git", "--", "git", ""]).is_err());
+        assert!(run("fish", &["/bin/git", "--", "git", "--unknown", ""]).is_err());
+        assert!(run("fish", &["/bin/git", "git", ""]).is_err());
+    }
+
+    #[test]
+    fn test_negation() {
+        use supplement::CompletionGroup;
+        use supplement::seen::{FlagState, SeenUnit};
+
+        let _ = env_logger::try_init();
+
+        let args = ["git", "log", "--color", "--"].map(|s| s.to_owned());
+        let (seen, grp) = Git::supplement(args.into_iter()).unwrap();
+        let CompletionGroup::Ready(ready) = grp else {
+            panic!("{grp:?} is unready")
+        };
+        // `--no-color` is in conflict, so it can't override `--color`
+        let comps = ready.into_inner().0;
+        assert!(comps.iter().all(|c| c.value != "--no-color"));
+
+        let [SeenUnit::No(unit)] = &*seen.into_inner() else {
+            panic!()
+        };
+        let args = ["git", "log", "--no-color", ""].map(|s| s.to_owned());
+        let (seen, grp) = Git::supplement(args.into_iter()).unwrap();
+        assert_eq!(seen.flag_state(unit.id), FlagState::Disabled);
+
+        let CompletionGroup::Unready { id, .. } = grp else {
+            panic!("{grp:?} is ready")
+        };
+        let id!(GitID.sub SubID.Log.commit(log_acc)) = id else {
+            panic!("unexpected id {id:?}")
+        };
+        assert_eq!(log_acc.color(&seen), 0);
+        assert_eq!(log_acc.no_color(&seen), 1);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        use supplement::CompletionGroup;
+
+        let _ = env_logger::try_init();
+
+        let args = ["git", "log", "--pretty=full", "--"].map(|s| s.to_owned());
+        let (_, grp) = Git::supplement(args.into_iter()).unwrap();
+        let CompletionGroup::Ready(ready) = grp else {
+            panic!("{grp:?} is unready")
+        };
+        let comps = ready.into_inner().0;
+        assert!(comps.iter().any(|c| c.value == "--color"));
+        assert!(comps.iter().all(|c| c.value != "--oneline"));
+    }
+
+    #[test]
+    fn test_required() {
+        use supplement::CompletionGroup;
+
+        let _ = env_logger::try_init();
+
+        let args = ["git", "cherry-pick", "--"].map(|s| s.to_owned());
+        let (_, grp) = Git::supplement(args.into_iter()).unwrap();
+        let CompletionGroup::Ready(ready) = grp else {
+            panic!("{grp:?} is unready")
+        };
+        let comps = ready.into_inner().0;
+        let required: Vec<_> = comps.iter().filter(|c| c.priority > 0).collect();
+        assert_eq!(required.len(), 1);
+        assert_eq!(required[0].value, "--mainline");
+        assert!(required[0].description.starts_with("(required) "));
+    }
+
+    #[test]
+    fn test_hidden() {
+        use supplement::CompletionGroup;
+        use supplement::core::Hidden;
+
+        let _ = env_logger::try_init();
+
+        let complete_with = |hidden: Hidden, last: &str| {
+            let args = ["git", "log", last].map(|s| s.to_owned());
+            let (_, grp) = Git::gen_cmd_with(hidden)
+                .supplement(args.into_iter())
+                .unwrap();
+            let CompletionGroup::Ready(ready) = grp else {
+                panic!("{grp:?} is unready")
+            };
+            let comps = ready.into_inner().0;
+            comps.iter().any(|c| c.value == "--debug")
+        };
+        let complete = |last: &str| complete_with(Hidden::Prefixed, last);
+        assert!(!complete("--"));
+        assert!(complete("--deb"));
+
+        assert!(!complete_with(Hidden::Always, "--deb"));
+        assert!(complete_with(Hidden::No, "--"));
+    }
+
+    #[test]
+    fn test_last_arg() {
+        use supplement::CompletionGroup;
+
+        let _ = env_logger::try_init();
+
+        let complete = |args: &[&str]| {
+            let args = args.iter().map(|s| s.to_string());
+            let (seen, grp) = Git::supplement(args).unwrap();
+            let CompletionGroup::Unready { id, .. } = grp else {
+                panic!("{grp:?} is ready")
+            };
+            (seen, id)
+        };
+        let (_, id) = complete(&["git", "diff", ""]);
+        assert!(matches!(id, id!(GitID.sub SubID.Diff.commit)));
+
+        // The optional `commit` is skipped after `--`
+        let (seen, id) = complete(&["git", "diff", "--", "a", ""]);
+        let id!(GitID.sub SubID.Diff.paths(diff_acc)) = id else {
+            panic!("unexpected id {id:?}")
+        };
+        assert_eq!(diff_acc.commit(&seen), None);
+        assert_eq!(
+            diff_acc.paths(&seen).collect::<Vec<_>>(),
+            vec![Path::new("a")]
+        );
+    }
+
     #[test]
     fn test_gen_cmd() {
         let _ = env_logger::try_init();
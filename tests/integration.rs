@@ -15,6 +15,9 @@ mod def {
         D,
         OPT2,
         E,
+        Point,
+        Files,
+        Features,
     }
 
     pub const C_FLAG_ID: id::NoVal = id::NoVal::new(line!());
@@ -22,8 +25,13 @@ mod def {
         ty: flag_type::Type::new_bool(C_FLAG_ID),
         short: CowSlice::Borrow(&['c']),
         long: CowOwned::Borrow(&["long-c", "long-c-2"]),
+        hidden_short: CowSlice::Borrow(&['C']),
+        hidden_long: CowOwned::Borrow(&["hidden-c"]),
+        hidden: Hidden::No,
         description: Cow::Borrowed("test description for flag C"),
         once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     pub const B_FLAG_ID: id::SingleVal = id::SingleVal::new(line!());
     pub const B_FLAG: Flag<ID> = Flag {
@@ -35,43 +43,250 @@ mod def {
         ),
         short: CowSlice::Borrow(&['b', 'x']),
         long: CowOwned::Borrow(&["long-b"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
         description: Cow::Borrowed("test description for flag B"),
         once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     pub const A_ARG_ID: id::SingleVal = id::SingleVal::new(line!());
     pub const A_ARG: Arg<ID> = Arg {
         id: Some(ID::A),
         seen_id: A_ARG_ID.into(),
         max_values: 1,
+        last: false,
+        value_delimiter: None,
         possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     pub const E_ARG_ID: id::SingleVal = id::SingleVal::new(line!());
     pub const E_ARG: Arg<ID> = Arg {
         id: Some(ID::E),
         seen_id: E_ARG_ID.into(),
         max_values: 1,
+        last: false,
+        value_delimiter: None,
         possible_values: CowOwned::Borrow(&[("ext1", "")]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
     pub const ROOT: Command<ID> = Command {
         all_flags: CowSlice::Borrow(&[B_FLAG, C_FLAG, OPT_FLAG]),
         name: Cow::Borrowed("root"),
         description: Cow::Borrowed(""),
+        aliases: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
         args: CowSlice::Borrow(&[E_ARG, D_ARG]),
         commands: CowSlice::Borrow(&[SUB]),
+        infer_long_args: false,
+        infer_subcommands: false,
     };
     pub const SUB: Command<ID> = Command {
-        all_flags: CowSlice::Borrow(&[B_FLAG, OPT2_FLAG]),
+        all_flags: CowSlice::Borrow(&[
+            B_FLAG,
+            OPT2_FLAG,
+            POINT_FLAG,
+            FILES_FLAG,
+            FEATURES_FLAG,
+            VERBOSE_FLAG,
+            NO_VERBOSE_FLAG,
+            QUIET_FLAG,
+        ]),
         name: Cow::Borrowed("sub"),
         description: Cow::Borrowed("test sub description"),
+        aliases: CowOwned::Borrow(&["alias-sub"]),
+        hidden: Hidden::No,
         args: CowSlice::Borrow(&[A_ARG, A_ARG]),
         commands: CowSlice::Borrow(&[]),
+        infer_long_args: false,
+        infer_subcommands: false,
+    };
+    pub const INFER_ROOT: Command<ID> = Command {
+        all_flags: CowSlice::Borrow(&[B_FLAG, C_FLAG, OPT_FLAG]),
+        name: Cow::Borrowed("root"),
+        description: Cow::Borrowed(""),
+        aliases: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        args: CowSlice::Borrow(&[E_ARG, D_ARG]),
+        commands: CowSlice::Borrow(&[INFER_SUB]),
+        infer_long_args: false,
+        infer_subcommands: true,
+    };
+    pub const INFER_SUB: Command<ID> = Command {
+        all_flags: CowSlice::Borrow(&[B_FLAG, OPT2_FLAG, POINT_FLAG, FILES_FLAG, FEATURES_FLAG]),
+        name: Cow::Borrowed("sub"),
+        description: Cow::Borrowed("test sub description"),
+        aliases: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        args: CowSlice::Borrow(&[A_ARG, A_ARG]),
+        commands: CowSlice::Borrow(&[]),
+        infer_long_args: true,
+        infer_subcommands: false,
+    };
+    pub const CONFLICT_CMD: Command<ID> = Command {
+        all_flags: CowSlice::Borrow(&[JSON_FLAG, YAML_FLAG, C_FLAG]),
+        name: Cow::Borrowed("conflict"),
+        description: Cow::Borrowed(""),
+        aliases: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        args: CowSlice::Borrow(&[PATH_ARG]),
+        commands: CowSlice::Borrow(&[]),
+        infer_long_args: false,
+        infer_subcommands: false,
+    };
+    pub const HIDDEN_ROOT: Command<ID> = Command {
+        all_flags: CowSlice::Borrow(&[C_FLAG, DEBUG_FLAG, TRACE_FLAG]),
+        name: Cow::Borrowed("hidden"),
+        description: Cow::Borrowed(""),
+        aliases: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        args: CowSlice::Borrow(&[]),
+        commands: CowSlice::Borrow(&[CONFLICT_CMD, INTERNAL_CMD]),
+        infer_long_args: false,
+        infer_subcommands: false,
+    };
+    pub const INTERNAL_CMD: Command<ID> = Command {
+        all_flags: CowSlice::Borrow(&[DEBUG_FLAG]),
+        name: Cow::Borrowed("internal"),
+        description: Cow::Borrowed(""),
+        aliases: CowOwned::Borrow(&[]),
+        hidden: Hidden::Prefixed,
+        args: CowSlice::Borrow(&[]),
+        commands: CowSlice::Borrow(&[]),
+        infer_long_args: false,
+        infer_subcommands: false,
+    };
+    pub const DEBUG_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const DEBUG_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_bool(DEBUG_FLAG_ID),
+        short: CowSlice::Borrow(&['d']),
+        long: CowOwned::Borrow(&["debug"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::Prefixed,
+        description: Cow::Borrowed(""),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const TRACE_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const TRACE_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_bool(TRACE_FLAG_ID),
+        short: CowSlice::Borrow(&[]),
+        long: CowOwned::Borrow(&["trace"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::Always,
+        description: Cow::Borrowed(""),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const REQUIRED_CMD: Command<ID> = Command {
+        all_flags: CowSlice::Borrow(&[OUT_FLAG, SIGN_FLAG]),
+        name: Cow::Borrowed("required"),
+        description: Cow::Borrowed(""),
+        aliases: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        args: CowSlice::Borrow(&[FILE_ARG]),
+        commands: CowSlice::Borrow(&[]),
+        infer_long_args: false,
+        infer_subcommands: false,
+    };
+    pub const OUT_FLAG_ID: id::SingleVal = id::SingleVal::new(line!());
+    pub const OUT_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_valued(
+            Some(ID::B),
+            OUT_FLAG_ID.into(),
+            CompleteWithEqual::NoNeed,
+            CowOwned::Borrow(&[]),
+        ),
+        short: CowSlice::Borrow(&['o']),
+        long: CowOwned::Borrow(&["out"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed("output"),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[Required::Always]),
+    };
+    pub const SIGN_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const SIGN_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_bool(SIGN_FLAG_ID),
+        short: CowSlice::Borrow(&['s']),
+        long: CowOwned::Borrow(&["sign"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed(""),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const FILE_ARG_ID: id::SingleVal = id::SingleVal::new(line!());
+    pub const FILE_ARG: Arg<ID> = Arg {
+        id: Some(ID::A),
+        seen_id: FILE_ARG_ID.into(),
+        max_values: 1,
+        last: false,
+        value_delimiter: None,
+        possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[Required::UnlessPresent(CowSlice::Borrow(&[
+            SIGN_FLAG_ID.any()
+        ]))]),
+    };
+    pub const JSON_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const JSON_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_bool(JSON_FLAG_ID),
+        short: CowSlice::Borrow(&[]),
+        long: CowOwned::Borrow(&["json"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed(""),
+        once: false,
+        conflicts: CowSlice::Borrow(&[YAML_FLAG_ID.any(), PATH_ARG_ID.any()]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const YAML_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const YAML_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_bool(YAML_FLAG_ID),
+        short: CowSlice::Borrow(&[]),
+        long: CowOwned::Borrow(&["yaml"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed(""),
+        once: true,
+        conflicts: CowSlice::Borrow(&[JSON_FLAG_ID.any()]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const PATH_ARG_ID: id::SingleVal = id::SingleVal::new(line!());
+    pub const PATH_ARG: Arg<ID> = Arg {
+        id: Some(ID::A),
+        seen_id: PATH_ARG_ID.into(),
+        max_values: 1,
+        last: false,
+        value_delimiter: None,
+        possible_values: CowOwned::Borrow(&[]),
+        conflicts: CowSlice::Borrow(&[JSON_FLAG_ID.any()]),
+        required: CowSlice::Borrow(&[]),
     };
     pub const D_ARG_ID: id::MultiVal = id::MultiVal::new(line!());
     pub const D_ARG: Arg<ID> = Arg {
         id: Some(ID::D),
         seen_id: D_ARG_ID.into(),
         max_values: 2,
+        last: false,
+        value_delimiter: None,
         possible_values: CowOwned::Borrow(&[("p1", "")]),
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
 
     pub const OPT_FLAG_ID: id::SingleVal = id::SingleVal::new(line!());
@@ -84,8 +299,120 @@ mod def {
         ),
         short: CowSlice::Borrow(&['o']),
         long: CowOwned::Borrow(&["opt"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
         description: Cow::Borrowed("test description for flag OPT"),
         once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+
+    pub const POINT_FLAG_ID: id::MultiVal = id::MultiVal::new(line!());
+    pub const POINT_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_multi_valued(
+            Some(ID::Point),
+            POINT_FLAG_ID.into(),
+            CompleteWithEqual::NoNeed,
+            CowOwned::Borrow(&[]),
+            2,
+            2,
+            None,
+        ),
+        short: CowSlice::Borrow(&['p']),
+        long: CowOwned::Borrow(&["point"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed("test description for flag POINT"),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const FILES_FLAG_ID: id::MultiVal = id::MultiVal::new(line!());
+    pub const FILES_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_multi_valued(
+            Some(ID::Files),
+            FILES_FLAG_ID.into(),
+            CompleteWithEqual::NoNeed,
+            CowOwned::Borrow(&[]),
+            1,
+            usize::MAX,
+            None,
+        ),
+        short: CowSlice::Borrow(&[]),
+        long: CowOwned::Borrow(&["files"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed("test description for flag FILES"),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+
+    pub const FEATURES_FLAG_ID: id::MultiVal = id::MultiVal::new(line!());
+    pub const FEATURES_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_multi_valued(
+            Some(ID::Features),
+            FEATURES_FLAG_ID.into(),
+            CompleteWithEqual::NoNeed,
+            CowOwned::Borrow(&[("f1", ""), ("f2", ""), ("f3", "")]),
+            1,
+            1,
+            Some(','),
+        ),
+        short: CowSlice::Borrow(&['F']),
+        long: CowOwned::Borrow(&["features"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed("test description for flag FEATURES"),
+        once: false,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+
+    pub const VERBOSE_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const VERBOSE_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_negatable(VERBOSE_FLAG_ID, NO_VERBOSE_FLAG_ID),
+        short: CowSlice::Borrow(&[]),
+        long: CowOwned::Borrow(&["verbose"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed("test description for flag VERBOSE"),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const NO_VERBOSE_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const NO_VERBOSE_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_negation(NO_VERBOSE_FLAG_ID, VERBOSE_FLAG_ID),
+        short: CowSlice::Borrow(&[]),
+        long: CowOwned::Borrow(&["no-verbose"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed("test description for flag NO_VERBOSE"),
+        once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
+    };
+    pub const QUIET_FLAG_ID: id::NoVal = id::NoVal::new(line!());
+    pub const QUIET_FLAG: Flag<ID> = Flag {
+        ty: flag_type::Type::new_bool(QUIET_FLAG_ID),
+        short: CowSlice::Borrow(&[]),
+        long: CowOwned::Borrow(&["quiet"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
+        description: Cow::Borrowed("test description for flag QUIET"),
+        once: true,
+        conflicts: CowSlice::Borrow(&[VERBOSE_FLAG_ID.any()]),
+        required: CowSlice::Borrow(&[Required::UnlessPresent(CowSlice::Borrow(&[
+            VERBOSE_FLAG_ID.any(),
+        ]))]),
     };
 
     pub const OPT2_FLAG_ID: id::SingleVal = id::SingleVal::new(line!());
@@ -98,8 +425,13 @@ mod def {
         ),
         short: CowSlice::Borrow(&['o']),
         long: CowOwned::Borrow(&["opt"]),
+        hidden_short: CowSlice::Borrow(&[]),
+        hidden_long: CowOwned::Borrow(&[]),
+        hidden: Hidden::No,
         description: Cow::Borrowed("test description for flag OPT"),
         once: true,
+        conflicts: CowSlice::Borrow(&[]),
+        required: CowSlice::Borrow(&[]),
     };
 }
 use def::ID;
@@ -119,6 +451,17 @@ fn try_run(args: &str, last_is_empty: bool) -> (Vec<SeenUnit>, Result<Completion
     let res = def::ROOT.supplement_with_seen(&mut seen, args);
     (seen.into_inner(), res)
 }
+/// The word containing `|` is under the cursor, with the `|` removed.
+fn try_run_at(args: &str) -> (Vec<SeenUnit>, Result<CompletionGroup<ID>>) {
+    let _ = env_logger::try_init();
+
+    let cursor = args.split(' ').position(|s| s.contains('|')).unwrap() + 1;
+    let args = args.split(' ').map(|s| s.replace('|', ""));
+    let args = std::iter::once("whatever".to_owned()).chain(args);
+    let mut seen = Seen::new();
+    let res = def::ROOT.supplement_with_seen_at(&mut seen, args, cursor);
+    (seen.into_inner(), res)
+}
 fn run(args: &str, last_is_empty: bool) -> (Vec<SeenUnit>, CompletionGroup<ID>) {
     let (h, r) = try_run(args, last_is_empty);
     (h, r.unwrap())
@@ -153,6 +496,7 @@ macro_rules! no {
         SeenUnit::No(SeenUnitNoVal {
             id: def::$id,
             count: 1,
+            negated: false,
         })
     };
 }
@@ -218,6 +562,15 @@ fn test_once_flag() {
     let (h, r) = run("-b option -", false);
     assert_eq!(h, vec![single!(B_FLAG_ID, "option")]);
     assert_eq!(map_comp_values(&r), vec!["--long-c", "--opt", "--opt="]);
+
+    // No space after the equal sign
+    let CompletionGroup::Ready(r) = r else {
+        unreachable!()
+    };
+    for comp in r.inner().0 {
+        let suffix = comp.value.ends_with('=').then_some("");
+        assert_eq!(comp.suffix.as_deref(), suffix, "{}", comp.value);
+    }
 }
 
 #[test]
@@ -296,7 +649,20 @@ fn test_fall_back_and_var_len_arg() {
 fn test_flag_after_args() {
     let (h, r) = run("sub arg1 --", false);
     assert_eq!(h, vec![single!(A_ARG_ID, "arg1")]);
-    assert_eq!(map_comp_values(&r), vec!["--long-b", "--opt", "--opt="],);
+    assert_eq!(
+        map_comp_values(&r),
+        vec![
+            "--features",
+            "--files",
+            "--long-b",
+            "--no-verbose",
+            "--opt",
+            "--opt=",
+            "--point",
+            "--quiet",
+            "--verbose"
+        ],
+    );
 
     let (h, r) = run("sub arg1 --long-b flag1", false);
     assert_eq!(h, vec![single!(A_ARG_ID, "arg1")]);
@@ -310,6 +676,325 @@ fn test_flag_after_args() {
     assert_eq!(map_unready(&r), (ID::A, "", vec![], ""));
 }
 
+#[test]
+fn test_multi_valued_flag() {
+    let (h, r) = run("sub --point 1", false);
+    assert_eq!(h, vec![]);
+    assert_eq!(map_unready(&r), (ID::Point, "1", vec![], ""));
+
+    let (h, r) = run("sub -p 1", true);
+    assert_eq!(h, vec![multi!(POINT_FLAG_ID, ["1"])]);
+    assert_eq!(map_unready(&r), (ID::Point, "", vec![], ""));
+
+    let (h, r) = run("sub --point 1 2", true);
+    assert_eq!(h, vec![multi!(POINT_FLAG_ID, ["1", "2"])]);
+    assert_eq!(map_unready(&r), (ID::A, "", vec![], ""));
+
+    let (_, r) = try_run("sub --point 1 --long-b x", true);
+    assert_eq!(r.unwrap_err(), Error::FlagNoValue("point".to_owned()));
+
+    // Optional values go on until a flag appears
+    let (h, r) = run("sub --files a b", true);
+    assert_eq!(h, vec![multi!(FILES_FLAG_ID, ["a", "b"])]);
+    assert_eq!(map_unready(&r), (ID::Files, "", vec![], ""));
+
+    let (h, r) = run("sub --files a --long-b x", true);
+    assert_eq!(
+        h,
+        vec![multi!(FILES_FLAG_ID, ["a"]), single!(B_FLAG_ID, "x")]
+    );
+    assert_eq!(map_unready(&r), (ID::A, "", vec![], ""));
+
+    let flags = vec![
+        "--features",
+        "--long-b",
+        "--no-verbose",
+        "--opt",
+        "--opt=",
+        "--point",
+        "--quiet",
+        "--verbose",
+    ];
+    let (h, r) = run("sub --files a --", false);
+    assert_eq!(h, vec![multi!(FILES_FLAG_ID, ["a"])]);
+    assert_eq!(map_comp_values(&r), flags);
+
+    let (h, r) = run("sub --files a -", false);
+    assert_eq!(h, vec![multi!(FILES_FLAG_ID, ["a"])]);
+    assert_eq!(map_comp_values(&r), flags);
+
+    // But not before the first value
+    let (h, r) = run("sub --files -", false);
+    assert_eq!(h, vec![]);
+    assert_eq!(map_unready(&r), (ID::Files, "-", vec![], ""));
+}
+
+#[test]
+fn test_value_delimiter() {
+    let (h, r) = run("sub --features f1,", false);
+    assert_eq!(h, vec![]);
+    assert_eq!(map_unready(&r), (ID::Features, "", vec!["f2", "f3"], "f1,"));
+
+    let (h, r) = run("sub --features=f3,x,f", false);
+    assert_eq!(h, vec![]);
+    assert_eq!(
+        map_unready(&r),
+        (ID::Features, "f", vec!["f1", "f2"], "--features=f3,x,")
+    );
+
+    // Only the last segment is matched
+    let (_, r) = run("sub --features=f1,f", false);
+    let CompletionGroup::Unready { unready, .. } = r else {
+        unreachable!()
+    };
+    let mut out = vec![];
+    unready
+        .to_ready(vec![])
+        .print(Shell::Fish, &mut out)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "--features=f1,f2\t\n--features=f1,f3\t\n"
+    );
+
+    let (h, r) = run("sub -F f1,f2 --features=f3", true);
+    assert_eq!(h, vec![multi!(FEATURES_FLAG_ID, ["f1", "f2", "f3"])]);
+    assert_eq!(map_unready(&r), (ID::A, "", vec![], ""));
+}
+
+#[test]
+fn test_end_of_options() {
+    let (h, r) = run("sub -- -", false);
+    assert_eq!(h, vec![]);
+    assert_eq!(map_unready(&r), (ID::A, "-", vec![], ""));
+
+    let (h, r) = run("sub -- --long-b", true);
+    assert_eq!(h, vec![single!(A_ARG_ID, "--long-b")]);
+    assert_eq!(map_unready(&r), (ID::A, "", vec![], ""));
+
+    // No subcommand after `--`
+    let (h, r) = run("-- s", false);
+    assert_eq!(h, vec![]);
+    assert_eq!(map_unready(&r), (ID::E, "s", vec!["ext1"], ""));
+
+    let (h, r) = run("-- sub", true);
+    assert_eq!(h, vec![single!(E_ARG_ID, "sub")]);
+    assert_eq!(map_unready(&r), (ID::D, "", vec!["p1"], ""));
+}
+
+#[test]
+fn test_alias() {
+    let (h, r) = run("--hidden-c alias-sub", true);
+    assert_eq!(h, vec![no!(C_FLAG_ID)]);
+    assert_eq!(map_unready(&r), (ID::A, "", vec![], ""));
+
+    let (h, r) = run("-C --opt=opt1 alias-sub", true);
+    assert_eq!(h, vec![no!(C_FLAG_ID), single!(OPT_FLAG_ID, "opt1")]);
+    assert_eq!(map_unready(&r), (ID::A, "", vec![], ""));
+
+    // Hidden aliases are never completed
+    let (_, r) = run("--", false);
+    assert_eq!(
+        map_comp_values(&r),
+        vec!["--long-b", "--long-c", "--opt", "--opt="]
+    );
+    let (_, r) = run("", false);
+    assert_eq!(map_unready(&r), (ID::E, "", vec!["ext1", "sub"], ""));
+}
+
+#[test]
+fn test_negation() {
+    macro_rules! negated {
+        ($id:ident, $count:expr) => {
+            SeenUnit::No(SeenUnitNoVal {
+                id: def::$id,
+                count: $count,
+                negated: true,
+            })
+        };
+    }
+
+    let (h, r) = run("sub --verbose --", false);
+    assert_eq!(h, vec![no!(VERBOSE_FLAG_ID)]);
+    let comps = map_comp_values(&r);
+    assert!(!comps.contains(&"--verbose") && !comps.contains(&"--no-verbose"));
+
+    let (h, r) = run("sub --no-verbose --", false);
+    assert_eq!(
+        h,
+        vec![no!(NO_VERBOSE_FLAG_ID), negated!(VERBOSE_FLAG_ID, 0)]
+    );
+    let comps = map_comp_values(&r);
+    assert!(!comps.contains(&"--verbose") && !comps.contains(&"--no-verbose"));
+    // `--verbose` is off, so `--quiet` is neither in conflict nor optional
+    let CompletionGroup::Ready(ready) = &r else {
+        panic!("{r:?} is unready");
+    };
+    let quiet = ready.inner().0.iter().find(|c| c.value == "--quiet");
+    assert!(quiet.unwrap().description.starts_with("(required) "));
+
+    let (_, r) = run("sub --verbose --", false);
+    assert!(!map_comp_values(&r).contains(&"--quiet"));
+
+    let (_, r) = try_run("sub --verbose --no-verbose", true);
+    assert_eq!(r.unwrap_err(), Error::FlagNotFound("no-verbose".to_owned()));
+
+    let mut seen = Seen::new();
+    let args = ["root", "sub", "--no-verbose", ""].map(|s| s.to_owned());
+    def::ROOT
+        .supplement_with_seen(&mut seen, args.into_iter())
+        .unwrap();
+    assert_eq!(seen.flag_state(def::VERBOSE_FLAG_ID), FlagState::Disabled);
+    assert_eq!(seen.flag_state(def::C_FLAG_ID), FlagState::Unset);
+    let sub = def::SUB;
+    let missing = sub.missing_flags(&seen);
+    let missing: Vec<_> = missing.map(|f| f.long.iter().next().unwrap()).collect();
+    assert_eq!(missing, vec!["quiet"]);
+}
+
+#[test]
+fn test_conflicts() {
+    let try_run = |args: &str| {
+        let args = ["whatever"].into_iter().chain(args.split(' '));
+        let mut seen = Seen::new();
+        let args = args.map(|s| s.to_owned());
+        let res = def::CONFLICT_CMD.supplement_with_seen(&mut seen, args);
+        (seen.into_inner(), res)
+    };
+
+    let (h, r) = try_run("--json --");
+    assert_eq!(h, vec![no!(JSON_FLAG_ID)]);
+    // `--json` can still repeat
+    assert_eq!(map_comp_values(&r.unwrap()), vec!["--json", "--long-c"]);
+
+    let (_, r) = try_run("--yaml --");
+    assert_eq!(map_comp_values(&r.unwrap()), vec!["--long-c"]);
+
+    let (_, r) = try_run("--json --yaml ");
+    assert_eq!(r.unwrap_err(), Error::FlagNotFound("yaml".to_owned()));
+
+    // No completion for the arg in conflict
+    let (_, r) = try_run("--json ");
+    assert_eq!(r.unwrap_err(), Error::UnexpectedArg("".to_owned()));
+    let (h, r) = try_run("-c ");
+    assert_eq!(h, vec![no!(C_FLAG_ID)]);
+    assert_eq!(map_unready(&r.unwrap()), (ID::A, "", vec![], ""));
+
+    let (h, r) = try_run("path --");
+    assert_eq!(h, vec![single!(PATH_ARG_ID, "path")]);
+    assert_eq!(map_comp_values(&r.unwrap()), vec!["--long-c", "--yaml"]);
+}
+
+#[test]
+fn test_required() {
+    let try_run = |args: &str| {
+        let args = ["whatever"].into_iter().chain(args.split(' '));
+        let mut seen = Seen::new();
+        let args = args.map(|s| s.to_owned());
+        let res = def::REQUIRED_CMD.supplement_with_seen(&mut seen, args);
+        let missing_flags: Vec<_> = def::REQUIRED_CMD
+            .missing_flags(&seen)
+            .map(|f| f.long.iter().next().unwrap().to_owned())
+            .collect();
+        let missing_args = def::REQUIRED_CMD.missing_args(&seen).count();
+        (missing_flags, missing_args, res.unwrap())
+    };
+    let required = |grp: &CompletionGroup<ID>| -> Vec<String> {
+        let CompletionGroup::Ready(r) = grp else {
+            panic!("{grp:?} is unready");
+        };
+        let comps = r.inner().0.iter().filter(|c| c.priority > 0);
+        let mut v: Vec<_> = comps.map(|c| c.value.clone()).collect();
+        v.sort();
+        v
+    };
+
+    let (flags, args, r) = try_run("--");
+    assert_eq!(flags, vec!["out"]);
+    assert_eq!(args, 1);
+    assert_eq!(required(&r), vec!["--out"]);
+    let CompletionGroup::Ready(ready) = &r else {
+        unreachable!()
+    };
+    let out = ready.inner().0.iter().find(|c| c.value == "--out");
+    assert_eq!(out.unwrap().description, "(required) output");
+
+    let (flags, args, r) = try_run("--sign -");
+    assert_eq!(flags, vec!["out"]);
+    assert_eq!(args, 0);
+    assert_eq!(required(&r), vec!["--out"]);
+
+    // `-s` is already in the chain
+    let (_, _, r) = try_run("-s");
+    assert_eq!(required(&r), vec!["-so"]);
+
+    let (flags, args, r) = try_run("-o x --");
+    assert!(flags.is_empty());
+    assert_eq!(args, 1);
+    assert!(required(&r).is_empty());
+}
+
+#[test]
+fn test_hidden() {
+    let try_run = |args: &str| {
+        let args = ["whatever"].into_iter().chain(args.split(' '));
+        let mut seen = Seen::new();
+        let args = args.map(|s| s.to_owned());
+        let res = def::HIDDEN_ROOT.supplement_with_seen(&mut seen, args);
+        (seen.into_inner(), res.unwrap())
+    };
+
+    let (_, r) = try_run("--");
+    assert_eq!(map_comp_values(&r), vec!["--long-c"]);
+    let (_, r) = try_run("-");
+    assert_eq!(map_comp_values(&r), vec!["--long-c"]);
+    let (_, r) = try_run("--de");
+    assert_eq!(map_comp_values(&r), vec!["--debug", "--long-c"]);
+    let (_, r) = try_run("--tr");
+    assert_eq!(map_comp_values(&r), vec!["--long-c"]);
+
+    // Still parsed
+    let (h, r) = try_run("-d --trace --");
+    assert_eq!(h, vec![no!(DEBUG_FLAG_ID), no!(TRACE_FLAG_ID)]);
+    assert_eq!(map_comp_values(&r), vec!["--long-c"]);
+
+    let (_, r) = try_run("");
+    assert_eq!(map_comp_values(&r), vec!["conflict"]);
+    let (_, r) = try_run("in");
+    assert_eq!(map_comp_values(&r), vec!["conflict", "internal"]);
+    let (_, r) = try_run("internal --d");
+    assert_eq!(map_comp_values(&r), vec!["--debug"]);
+}
+
+#[test]
+fn test_infer() {
+    let try_run = |args: &str| {
+        let args = ["whatever"].into_iter().chain(args.split(' '));
+        let mut seen = Seen::new();
+        let res = def::INFER_ROOT.supplement_with_seen(&mut seen, args.map(|s| s.to_owned()));
+        (seen.into_inner(), res)
+    };
+
+    let (h, r) = try_run("s --fi x --lo b1 ");
+    assert_eq!(
+        h,
+        vec![multi!(FILES_FLAG_ID, ["x"]), single!(B_FLAG_ID, "b1")]
+    );
+    assert_eq!(map_unready(&r.unwrap()), (ID::A, "", vec![], ""));
+
+    // Ambiguous between `--files` and `--features`
+    let (_, r) = try_run("su --f x ");
+    assert!(matches!(r, Err(Error::FlagNotFound(f)) if f == "f"));
+
+    // Not inferred if the flag is already seen
+    let (_, r) = try_run("sub --long-b b1 --lo b2 ");
+    assert!(matches!(r, Err(Error::FlagNotFound(f)) if f == "lo"));
+
+    // Only the subcommand infers long flags
+    let (_, r) = try_run("--lo b1 ");
+    assert!(matches!(r, Err(Error::FlagNotFound(f)) if f == "lo"));
+}
+
 #[test]
 fn test_flag_after_external_sub() {
     let (h, r) = run("--long-b flag1 ext", true);
@@ -381,6 +1066,20 @@ fn test_optional_flag() {
     assert_eq!(h, vec![no!(C_FLAG_ID)]);
     assert_eq!(map_comp_values(&r), vec!["-co=opt1", "-co=opt2"]);
 
+    let print = |cmd| {
+        let (_, r) = run(cmd, false);
+        let CompletionGroup::Ready(ready) = r else {
+            unreachable!()
+        };
+        let mut out = vec![];
+        ready.print(Shell::Fish, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    assert_eq!(print("-o"), "-o=opt1\t\n-o=opt2\t\n");
+    assert_eq!(print("-co"), "-co=opt1\t\n-co=opt2\t\n");
+    assert_eq!(print("-co="), "-co=opt1\t\n-co=opt2\t\n");
+    assert_eq!(print("-co=opt2"), "-co=opt2\t\n");
+
     let (h, r) = try_run("-oz", false);
     assert_eq!(h, vec![single!(OPT_FLAG_ID, "")]);
     assert_eq!(r.unwrap_err(), Error::FlagNotFound("z".to_owned()));
@@ -390,7 +1089,20 @@ fn test_optional_flag() {
 fn test_custom_with_possible() {
     let (h, r) = run("sub -", false);
     assert_eq!(h, vec![]);
-    assert_eq!(map_comp_values(&r), vec!["--long-b", "--opt", "--opt="]);
+    assert_eq!(
+        map_comp_values(&r),
+        vec![
+            "--features",
+            "--files",
+            "--long-b",
+            "--no-verbose",
+            "--opt",
+            "--opt=",
+            "--point",
+            "--quiet",
+            "--verbose"
+        ]
+    );
 
     let (h, r) = run("sub --opt=x", false);
     assert_eq!(h, vec![]);
@@ -403,3 +1115,35 @@ fn test_custom_with_possible() {
     assert_eq!(h, vec![]);
     assert_eq!(map_unready(&r), (ID::OPT2, "", vec!["opt3", "opt4"], "-o="));
 }
+
+#[test]
+fn test_cursor_in_middle() {
+    let (h, r) = try_run_at("sub | a2 --long-b x");
+    assert_eq!(h, vec![single!(A_ARG_ID, "a2"), single!(B_FLAG_ID, "x")]);
+    assert_eq!(map_unready(&r.unwrap()), (ID::A, "", vec![], ""));
+
+    let (h, r) = try_run_at("-| -c sub");
+    assert_eq!(h, vec![no!(C_FLAG_ID)]);
+    assert_eq!(
+        map_comp_values(&r.unwrap()),
+        vec!["--long-b", "--long-c", "--opt", "--opt="],
+    );
+
+    let (h, r) = try_run_at("--long-b x| sub a1");
+    assert_eq!(h, vec![single!(A_ARG_ID, "a1")]);
+    assert_eq!(map_unready(&r.unwrap()), (ID::B, "x", vec![], ""));
+
+    // Nonsense after cursor doesn't matter
+    let (h, r) = try_run_at("sub a1 | --unknown --long-b");
+    assert_eq!(h, vec![single!(A_ARG_ID, "a1")]);
+    assert_eq!(map_unready(&r.unwrap()), (ID::A, "", vec![], ""));
+
+    // Same as `supplement` if the cursor is at the last word
+    let (h, r) = try_run_at("sub a1 |");
+    assert_eq!(h, vec![single!(A_ARG_ID, "a1")]);
+    assert_eq!(map_unready(&r.unwrap()), (ID::A, "", vec![], ""));
+
+    let args = ["whatever", "sub"].iter().map(|s| s.to_string());
+    let r = def::ROOT.supplement_at(args, 2);
+    assert_eq!(r.unwrap_err(), Error::ArgsTooShort);
+}